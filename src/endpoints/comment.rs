@@ -0,0 +1,374 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::{CommentId, StoryId, UserId};
+
+use super::ranking::{self, Hotness};
+use super::Page;
+use crate::lorem;
+use crate::queries::QueryCatalog;
+
+pub(crate) struct Comment {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) id: CommentId,
+    pub(crate) story: StoryId,
+    pub(crate) parent: Option<CommentId>,
+    /// Whether the user wears one of their hats for this comment, if they have any.
+    pub(crate) hat: bool,
+    pub(crate) priming: bool,
+}
+
+#[async_trait]
+impl Page for Comment {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Comment {
+            acting_as,
+            id,
+            story,
+            parent,
+            hat,
+            priming,
+        } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+        let story = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "comment.story",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&story[..]).unwrap(),),
+            )
+            .await?;
+        let mut story = story.unwrap();
+
+        // replies to a merged story end up on the story it was merged into, as do its comments
+        if let Some(merged_into) = story.get::<Option<u32>, _>("merged_story_id").unwrap() {
+            story = c
+                .exec_first::<Row, _, _>(
+                    q.get(
+                        "comment.merged_into",
+                        "SELECT `stories`.* \
+                         FROM `stories` \
+                         WHERE `stories`.`id` = ?",
+                    ),
+                    (merged_into,),
+                )
+                .await?
+                .unwrap();
+        }
+        let author = story.get::<u32, _>("user_id").unwrap();
+        let mut hotness = Hotness::of(&story);
+        let story = story.get::<u32, _>("id").unwrap();
+
+        if !priming {
+            c.exec_drop(
+                q.get(
+                    "comment.story_author",
+                    "SELECT `users`.* FROM `users` WHERE `users`.`id` = ?",
+                ),
+                (author,),
+            )
+            .await?;
+        }
+
+        let parent = if let Some(parent) = parent {
+            // check that parent exists
+            let p = c
+                .exec_first::<Row, _, _>(
+                    q.get(
+                        "comment.parent",
+                        "SELECT  `comments`.* FROM `comments` \
+                         WHERE `comments`.`story_id` = ? \
+                         AND `comments`.`short_id` = ?",
+                    ),
+                    (story, ::std::str::from_utf8(&parent[..]).unwrap()),
+                )
+                .await?;
+
+            if let Some(p) = p {
+                Some((
+                    p.get::<u32, _>("id").unwrap(),
+                    p.get::<Option<u64>, _>("thread_id").unwrap(),
+                ))
+            } else {
+                eprintln!(
+                    "failed to find parent comment {} in story {}",
+                    ::std::str::from_utf8(&parent[..]).unwrap(),
+                    story
+                );
+                None
+            }
+        } else {
+            None
+        };
+
+        // TODO: real site checks for recent comments by same author with same
+        // parent to ensure we don't double-post accidentally
+
+        if !priming {
+            // check that short id is available
+            c.exec_drop(
+                q.get(
+                    "comment.short_id_taken",
+                    "SELECT  1 AS one FROM `comments` \
+                     WHERE `comments`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&id[..]).unwrap(),),
+            )
+            .await?;
+        }
+
+        // TODO: real impl checks *new* short_id *again*
+
+        // a reply continues its parent's thread, anything else starts a new one
+        let (parent, thread) = match parent {
+            Some((parent, Some(thread))) => (Some(parent), thread),
+            _ => {
+                let q_thread = c
+                    .query_iter(q.get(
+                        "comment.next_thread_id",
+                        "INSERT INTO `keystores` (`key`, `value`) \
+                         VALUES ('thread_id', LAST_INSERT_ID(1)) \
+                         ON DUPLICATE KEY UPDATE \
+                         `keystores`.`value` = LAST_INSERT_ID(`keystores`.`value` + 1)",
+                    ))
+                    .await?;
+                let thread = q_thread.last_insert_id().unwrap();
+                q_thread.drop_result().await?;
+                (parent.map(|(parent, _)| parent), thread)
+            }
+        };
+
+        let hat = if hat {
+            c.exec_first::<u32, _, _>(
+                q.get(
+                    "comment.hat",
+                    "SELECT `hats`.`id` FROM `hats` \
+                     WHERE `hats`.`user_id` = ? \
+                     AND `hats`.`doffed_at` IS NULL \
+                     LIMIT 1",
+                ),
+                (user,),
+            )
+            .await?
+        } else {
+            None
+        };
+
+        // NOTE: MySQL technically does everything inside this and_then in a transaction,
+        // but let's be nice to it
+        let now = chrono::Local::now().naive_local();
+        let text = lorem::paragraph(&mut rand::thread_rng());
+        let markeddown = format!("<p>{}</p>\n", text);
+        let q_insert = if let Some(parent) = parent {
+            c.exec_iter(
+                q.get(
+                    "comment.insert_reply",
+                    "INSERT INTO `comments` \
+                     (`created_at`, `updated_at`, `short_id`, `story_id`, \
+                     `user_id`, `parent_comment_id`, `thread_id`, \
+                     `comment`, `upvotes`, `confidence`, \
+                     `markeddown_comment`, `hat_id`) \
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                ),
+                (
+                    now,
+                    now,
+                    ::std::str::from_utf8(&id[..]).unwrap(),
+                    story,
+                    user,
+                    parent,
+                    thread,
+                    &text,
+                    1,
+                    ranking::confidence(1, 0),
+                    &markeddown,
+                    hat,
+                ),
+            )
+            .await?
+        } else {
+            c.exec_iter(
+                q.get(
+                    "comment.insert",
+                    "INSERT INTO `comments` \
+                     (`created_at`, `updated_at`, `short_id`, `story_id`, \
+                     `user_id`, `thread_id`, `comment`, `upvotes`, `confidence`, \
+                     `markeddown_comment`, `hat_id`) \
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                ),
+                (
+                    now,
+                    now,
+                    ::std::str::from_utf8(&id[..]).unwrap(),
+                    story,
+                    user,
+                    thread,
+                    &text,
+                    1,
+                    ranking::confidence(1, 0),
+                    &markeddown,
+                    hat,
+                ),
+            )
+            .await?
+        };
+        let comment = q_insert.last_insert_id().unwrap();
+        q_insert.drop_result().await?;
+
+        if !priming {
+            // but why?!
+            c.exec_drop(
+                q.get(
+                    "comment.user_vote",
+                    "SELECT  `votes`.* FROM `votes` \
+                     WHERE `votes`.`user_id` = ? \
+                     AND `votes`.`story_id` = ? \
+                     AND `votes`.`comment_id` = ?",
+                ),
+                (user, story, comment),
+            )
+            .await?;
+        }
+
+        c.exec_drop(
+            q.get(
+                "comment.insert_vote",
+                "INSERT INTO `votes` \
+                 (`user_id`, `story_id`, `comment_id`, `vote`) \
+                 VALUES (?, ?, ?, ?)",
+            ),
+            (user, story, comment, 1),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "comment.merged_stories",
+                "SELECT `stories`.`id` \
+                 FROM `stories` \
+                 WHERE `stories`.`merged_story_id` = ?",
+            ),
+            (story,),
+        )
+        .await?;
+
+        // why are these ordered?
+        let count = c
+            .exec_iter(
+                q.get(
+                    "comment.story_comments",
+                    "SELECT `comments`.*, \
+                     `comments`.`upvotes` - `comments`.`downvotes` AS saldo \
+                     FROM `comments` \
+                     WHERE `comments`.`story_id` = ? \
+                     ORDER BY \
+                     saldo ASC, \
+                     confidence DESC",
+                ),
+                (story,),
+            )
+            .await?
+            .reduce_and_drop(0, |rows, _: Row| rows + 1)
+            .await?;
+
+        c.exec_drop(
+            q.get(
+                "comment.update_comments_count",
+                "UPDATE `stories` \
+                 SET `comments_count` = ?
+                 WHERE `stories`.`id` = ?",
+            ),
+            (count, story),
+        )
+        .await?;
+
+        // during priming, the hotness is recomputed from the story alone rather than paying for
+        // the queries below on every comment
+        if !priming {
+            // get all the stuff needed to compute updated hotness
+            hotness.tags_mod = c
+                .exec_iter(
+                    q.get(
+                        "comment.tags",
+                        "SELECT `tags`.* \
+                         FROM `tags` \
+                         INNER JOIN `taggings` \
+                         ON `tags`.`id` = `taggings`.`tag_id` \
+                         WHERE `taggings`.`story_id` = ?",
+                    ),
+                    (story,),
+                )
+                .await?
+                .reduce_and_drop(0.0, |tags_mod, tag: Row| {
+                    tags_mod + ranking::hotness_mod(&tag)
+                })
+                .await?;
+
+            hotness.comment_votes = c
+                .exec_iter(
+                    q.get(
+                        "comment.comment_votes",
+                        "SELECT \
+                         `comments`.`upvotes`, \
+                         `comments`.`downvotes` \
+                         FROM `comments` \
+                         JOIN `stories` ON (`stories`.`id` = `comments`.`story_id`) \
+                         WHERE `comments`.`story_id` = ? \
+                         AND `comments`.`user_id` <> `stories`.`user_id`",
+                    ),
+                    (story,),
+                )
+                .await?
+                .map_and_drop(|comment: Row| ranking::votes(&comment))
+                .await?;
+
+            hotness.merged_votes = c
+                .exec_iter(
+                    q.get(
+                        "comment.merged_votes",
+                        "SELECT `stories`.`id`, `stories`.`upvotes`, `stories`.`downvotes` \
+                         FROM `stories` \
+                         WHERE `stories`.`merged_story_id` = ?",
+                    ),
+                    (story,),
+                )
+                .await?
+                .map_and_drop(|merged: Row| ranking::votes(&merged))
+                .await?;
+        }
+
+        // why oh why is story hotness *updated* here?!
+        c.exec_drop(
+            q.get(
+                "comment.update_hotness",
+                "UPDATE `stories` \
+                 SET `hotness` = ? \
+                 WHERE `stories`.`id` = ?",
+            ),
+            (hotness.compute(), story),
+        )
+        .await?;
+
+        let key = format!("user:{}:comments_posted", user);
+        c.exec_drop(
+            q.get(
+                "comment.update_comments_posted",
+                "INSERT INTO keystores (`key`, `value`) \
+                 VALUES (?, ?) \
+                 ON DUPLICATE KEY UPDATE `keystores`.`value` = `keystores`.`value` + 1",
+            ),
+            (key, 1),
+        )
+        .await?;
+
+        Ok((c, false))
+    }
+}
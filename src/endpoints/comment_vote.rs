@@ -0,0 +1,225 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::{CommentId, UserId, Vote};
+
+use super::ranking::{self, Hotness};
+use super::votes::{self, VoteChange};
+use super::Page;
+use crate::queries::QueryCatalog;
+
+pub(crate) struct CommentVote {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) comment: CommentId,
+    pub(crate) vote: Vote,
+    /// Withdraw the user's existing vote instead of voting `vote`.
+    pub(crate) unvote: bool,
+}
+
+#[async_trait]
+impl Page for CommentVote {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let CommentVote {
+            acting_as,
+            comment,
+            vote: v,
+            unvote,
+        } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let comment = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "comment_vote.comment",
+                    "SELECT `comments`.* \
+                     FROM `comments` \
+                     WHERE `comments`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&comment[..]).unwrap(),),
+            )
+            .await?;
+
+        let comment = comment.unwrap();
+        let author = comment.get::<u32, _>("user_id").unwrap();
+        let sid = comment.get::<u32, _>("story_id").unwrap();
+        let (upvotes, downvotes) = ranking::votes(&comment);
+        let comment = comment.get::<u32, _>("id").unwrap();
+        let existing = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "comment_vote.user_vote",
+                    "SELECT  `votes`.* \
+                     FROM `votes` \
+                     WHERE `votes`.`user_id` = ? \
+                     AND `votes`.`story_id` = ? \
+                     AND `votes`.`comment_id` = ?",
+                ),
+                (user, sid, comment),
+            )
+            .await?;
+
+        let change = VoteChange::new(existing.as_ref(), v, unvote);
+        change.record("comment");
+
+        // TODO: technically need to re-load comment under transaction
+
+        // NOTE: MySQL technically does everything inside this and_then in a transaction,
+        // but let's be nice to it
+        match change {
+            VoteChange::Noop => return Ok((c, false)),
+            VoteChange::New { up } => {
+                c.exec_drop(
+                    q.get(
+                        "comment_vote.insert_vote",
+                        "INSERT INTO `votes` \
+                         (`user_id`, `story_id`, `comment_id`, `vote`) \
+                         VALUES \
+                         (?, ?, ?, ?)",
+                    ),
+                    (user, sid, comment, up as u8),
+                )
+                .await?;
+            }
+            VoteChange::Flip { up } => {
+                c.exec_drop(
+                    q.get(
+                        "comment_vote.update_vote",
+                        "UPDATE `votes` \
+                         SET `votes`.`vote` = ? \
+                         WHERE `votes`.`id` = ?",
+                    ),
+                    (up as u8, existing.unwrap().get::<u64, _>("id").unwrap()),
+                )
+                .await?;
+            }
+            VoteChange::Unvote { .. } => {
+                c.exec_drop(
+                    q.get(
+                        "comment_vote.delete_vote",
+                        "DELETE FROM `votes` \
+                         WHERE `votes`.`id` = ?",
+                    ),
+                    (existing.unwrap().get::<u64, _>("id").unwrap(),),
+                )
+                .await?;
+            }
+        }
+
+        c.exec_drop(
+            q.get(
+                "comment_vote.update_karma",
+                "UPDATE `users` \
+                 SET `users`.`karma` = `users`.`karma` {karma} \
+                 WHERE `users`.`id` = ?",
+            )
+            .replace("{karma}", &votes::delta(change.karma())),
+            (author,),
+        )
+        .await?;
+
+        let (up, down) = change.deltas();
+        let confidence = ranking::confidence(upvotes + up, downvotes + down);
+        c.exec_drop(
+            q.get(
+                "comment_vote.update_comment",
+                "UPDATE `comments` \
+                 SET \
+                 `comments`.`upvotes` = `comments`.`upvotes` {upvotes}, \
+                 `comments`.`downvotes` = `comments`.`downvotes` {downvotes}, \
+                 `comments`.`confidence` = ? \
+                 WHERE `id` = ?",
+            )
+            .replace("{upvotes}", &votes::delta(up))
+            .replace("{downvotes}", &votes::delta(down)),
+            (confidence, comment),
+        )
+        .await?;
+
+        // get all the stuff needed to compute updated hotness
+        let story = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "comment_vote.story",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`id` = ?",
+                ),
+                (sid,),
+            )
+            .await?;
+        let mut hotness = Hotness::of(&story.unwrap());
+        hotness.upvotes += up;
+        hotness.downvotes += down;
+
+        hotness.tags_mod = c
+            .exec_iter(
+                q.get(
+                    "comment_vote.tags",
+                    "SELECT `tags`.* \
+                     FROM `tags` \
+                     INNER JOIN `taggings` ON `tags`.`id` = `taggings`.`tag_id` \
+                     WHERE `taggings`.`story_id` = ?",
+                ),
+                (sid,),
+            )
+            .await?
+            .reduce_and_drop(0.0, |tags_mod, tag: Row| {
+                tags_mod + ranking::hotness_mod(&tag)
+            })
+            .await?;
+
+        hotness.comment_votes = c
+            .exec_iter(
+                q.get(
+                    "comment_vote.comment_votes",
+                    "SELECT \
+                     `comments`.`upvotes`, \
+                     `comments`.`downvotes` \
+                     FROM `comments` \
+                     JOIN `stories` ON (`stories`.`id` = `comments`.`story_id`) \
+                     WHERE `comments`.`story_id` = ? \
+                     AND `comments`.`user_id` <> `stories`.`user_id`",
+                ),
+                (sid,),
+            )
+            .await?
+            .map_and_drop(|comment: Row| ranking::votes(&comment))
+            .await?;
+
+        hotness.merged_votes = c
+            .exec_iter(
+                q.get(
+                    "comment_vote.merged_stories",
+                    "SELECT `stories`.`id`, `stories`.`upvotes`, `stories`.`downvotes` \
+                     FROM `stories` \
+                     WHERE `stories`.`merged_story_id` = ?",
+                ),
+                (sid,),
+            )
+            .await?
+            .map_and_drop(|merged: Row| ranking::votes(&merged))
+            .await?;
+
+        c.exec_drop(
+            q.get(
+                "comment_vote.update_story",
+                "UPDATE stories SET \
+                 stories.upvotes = stories.upvotes {upvotes}, \
+                 stories.downvotes = stories.downvotes {downvotes}, \
+                 stories.hotness = ? \
+                 WHERE id = ?",
+            )
+            .replace("{upvotes}", &votes::delta(up))
+            .replace("{downvotes}", &votes::delta(down)),
+            (hotness.compute(), sid),
+        )
+        .await?;
+
+        Ok((c, false))
+    }
+}
@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::HashSet;
+use std::future::Future;
+use std::iter;
+use trawler::UserId;
+
+use super::listing::ids_in;
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// How many comments `/comments` lists per page.
+const COMMENTS_PER_PAGE: u32 = 40;
+
+pub(crate) struct Comments {
+    pub(crate) acting_as: Option<UserId>,
+    /// Which page of the listing, starting at 0 for the first.
+    pub(crate) page: u32,
+}
+
+#[async_trait]
+impl Page for Comments {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Comments { acting_as, page } = self;
+        let mut c = c.await?;
+
+        let (comments, users, stories) = c
+            .exec_iter(
+                q.get(
+                    "comments.comments",
+                    "SELECT  `comments`.* \
+                     FROM `comments` \
+                     WHERE `comments`.`is_deleted` = 0 \
+                     AND `comments`.`is_moderated` = 0 \
+                     ORDER BY id DESC \
+                     LIMIT ? OFFSET ?",
+                ),
+                (COMMENTS_PER_PAGE, page * COMMENTS_PER_PAGE),
+            )
+            .await?
+            .reduce_and_drop(
+                (Vec::new(), HashSet::new(), HashSet::new()),
+                |(mut comments, mut users, mut stories), comment: Row| {
+                    comments.push(comment.get::<u32, _>("id").unwrap());
+                    users.insert(comment.get::<u32, _>("user_id").unwrap());
+                    stories.insert(comment.get::<u32, _>("story_id").unwrap());
+                    (comments, users, stories)
+                },
+            )
+            .await?;
+
+        // pages past the end of the listing are empty
+        if comments.is_empty() {
+            return Ok((c, true));
+        }
+
+        if let Some(uid) = acting_as {
+            let params = stories.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+            let args: Vec<_> = iter::once(&uid as &_)
+                .chain(stories.iter().map(|c| c as &_))
+                .collect();
+            c.exec_drop(
+                q.get(
+                    "comments.hidden",
+                    "SELECT 1 FROM hidden_stories \
+                     WHERE user_id = ? \
+                     AND hidden_stories.story_id IN ({stories})",
+                )
+                .replace("{stories}", &params),
+                args,
+            )
+            .await?;
+        }
+
+        let users = ids_in(&users);
+        c.query_drop(
+            q.get(
+                "comments.users",
+                "SELECT `users`.* FROM `users` \
+                 WHERE `users`.`id` IN ({users})",
+            )
+            .replace("{users}", &users),
+        )
+        .await?;
+
+        let stories = ids_in(&stories);
+
+        let authors = c
+            .query_iter(
+                q.get(
+                    "comments.stories",
+                    "SELECT  `stories`.* FROM `stories` \
+                     WHERE `stories`.`id` IN ({stories})",
+                )
+                .replace("{stories}", &stories),
+            )
+            .await?
+            .reduce_and_drop(HashSet::new(), |mut authors, story: Row| {
+                authors.insert(story.get::<u32, _>("user_id").unwrap());
+                authors
+            })
+            .await?;
+
+        if let Some(uid) = acting_as {
+            let params = comments.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+            let comments: Vec<_> = iter::once(&uid as &_)
+                .chain(comments.iter().map(|c| c as &_))
+                .collect();
+
+            c.exec_drop(
+                q.get(
+                    "comments.votes",
+                    "SELECT `votes`.* FROM `votes` \
+                     WHERE `votes`.`user_id` = ? \
+                     AND `votes`.`comment_id` IN ({comments})",
+                )
+                .replace("{comments}", &params),
+                comments,
+            )
+            .await?;
+        }
+
+        // NOTE: the real website issues all of these one by one...
+        let authors = ids_in(&authors);
+
+        c.query_drop(
+            q.get(
+                "comments.story_authors",
+                "SELECT  `users`.* FROM `users` \
+                 WHERE `users`.`id` IN ({users})",
+            )
+            .replace("{users}", &authors),
+        )
+        .await?;
+
+        Ok((c, true))
+    }
+}
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::{CommentId, UserId};
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user deleting one of their comments. The comment stays in the thread, but is no longer
+/// counted or shown.
+pub(crate) struct DeleteComment {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) comment: CommentId,
+}
+
+#[async_trait]
+impl Page for DeleteComment {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let DeleteComment { acting_as, comment } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let comment = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "delete_comment.comment",
+                    "SELECT `comments`.* \
+                     FROM `comments` \
+                     WHERE `comments`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&comment[..]).unwrap(),),
+            )
+            .await?;
+        let comment = match comment {
+            Some(comment) => comment,
+            None => return Ok((c, false)),
+        };
+
+        if comment.get::<u32, _>("user_id").unwrap() != user
+            || comment.get::<bool, _>("is_deleted").unwrap()
+        {
+            return Ok((c, false));
+        }
+        let story = comment.get::<u32, _>("story_id").unwrap();
+        let comment = comment.get::<u32, _>("id").unwrap();
+
+        let now = chrono::Local::now().naive_local();
+        c.exec_drop(
+            q.get(
+                "delete_comment.delete",
+                "UPDATE `comments` \
+                 SET `comments`.`is_deleted` = 1, \
+                 `comments`.`updated_at` = ? \
+                 WHERE `comments`.`id` = ?",
+            ),
+            (now, comment),
+        )
+        .await?;
+
+        let count = c
+            .exec_first::<u64, _, _>(
+                q.get(
+                    "delete_comment.active_comments",
+                    "SELECT COUNT(*) \
+                     FROM `comments` \
+                     WHERE `comments`.`story_id` = ? \
+                     AND `comments`.`is_deleted` = 0 \
+                     AND `comments`.`is_moderated` = 0",
+                ),
+                (story,),
+            )
+            .await?
+            .unwrap_or(0);
+
+        c.exec_drop(
+            q.get(
+                "delete_comment.update_comments_count",
+                "UPDATE `stories` \
+                 SET `comments_count` = ? \
+                 WHERE `stories`.`id` = ?",
+            ),
+            (count, story),
+        )
+        .await?;
+
+        Ok((c, false))
+    }
+}
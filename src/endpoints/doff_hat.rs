@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error};
+use std::future::Future;
+use trawler::UserId;
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user giving up one of their hats (e.g., after leaving a project), so it can no longer be
+/// worn. Comments posted with it keep it.
+pub(crate) struct DoffHat {
+    pub(crate) acting_as: Option<UserId>,
+}
+
+#[async_trait]
+impl Page for DoffHat {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let DoffHat { acting_as } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let hat = c
+            .exec_first::<u32, _, _>(
+                q.get(
+                    "doff_hat.hat",
+                    "SELECT `hats`.`id` FROM `hats` \
+                     WHERE `hats`.`user_id` = ? \
+                     AND `hats`.`doffed_at` IS NULL \
+                     ORDER BY `hats`.`id` ASC \
+                     LIMIT 1",
+                ),
+                (user,),
+            )
+            .await?;
+        let hat = match hat {
+            Some(hat) => hat,
+            None => return Ok((c, true)),
+        };
+
+        c.exec_drop(
+            q.get(
+                "doff_hat.doff",
+                "UPDATE `hats` \
+                 SET `hats`.`doffed_at` = ? \
+                 WHERE `hats`.`id` = ?",
+            ),
+            (chrono::Local::now().naive_local(), hat),
+        )
+        .await?;
+
+        Ok((c, true))
+    }
+}
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::{CommentId, UserId};
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user rewriting one of their comments, after which the real site re-renders just that
+/// comment.
+pub(crate) struct EditComment {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) comment: CommentId,
+    pub(crate) text: String,
+}
+
+#[async_trait]
+impl Page for EditComment {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let EditComment {
+            acting_as,
+            comment,
+            text,
+        } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let comment = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "edit_comment.comment",
+                    "SELECT `comments`.* \
+                     FROM `comments` \
+                     WHERE `comments`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&comment[..]).unwrap(),),
+            )
+            .await?;
+        let comment = match comment {
+            Some(comment) => comment,
+            None => return Ok((c, false)),
+        };
+
+        // only the author gets to edit, and only while the comment is still there
+        if comment.get::<u32, _>("user_id").unwrap() != user
+            || comment.get::<bool, _>("is_deleted").unwrap()
+        {
+            return Ok((c, false));
+        }
+        let story = comment.get::<u32, _>("story_id").unwrap();
+        let comment = comment.get::<u32, _>("id").unwrap();
+
+        let now = chrono::Local::now().naive_local();
+        c.exec_drop(
+            q.get(
+                "edit_comment.update",
+                "UPDATE `comments` \
+                 SET `comments`.`comment` = ?, \
+                 `comments`.`markeddown_comment` = ?, \
+                 `comments`.`updated_at` = ? \
+                 WHERE `comments`.`id` = ?",
+            ),
+            (&text, format!("<p>{}</p>\n", text), now, comment),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "edit_comment.story",
+                "SELECT `stories`.* FROM `stories` WHERE `stories`.`id` = ?",
+            ),
+            (story,),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "edit_comment.author",
+                "SELECT `users`.* FROM `users` WHERE `users`.`id` = ?",
+            ),
+            (user,),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "edit_comment.vote",
+                "SELECT `votes`.* FROM `votes` \
+                 WHERE `votes`.`user_id` = ? \
+                 AND `votes`.`story_id` = ? \
+                 AND `votes`.`comment_id` = ?",
+            ),
+            (user, story, comment),
+        )
+        .await?;
+
+        Ok((c, false))
+    }
+}
@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::{self, Future};
+use trawler::{StoryId, UserId};
+
+use super::story::Story;
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user opening the edit form of one of their stories and saving a new title and
+/// description, after which the real site redirects to the story page.
+pub(crate) struct EditStory {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) story: StoryId,
+    pub(crate) title: String,
+    pub(crate) description: String,
+}
+
+#[async_trait]
+impl Page for EditStory {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let EditStory {
+            acting_as,
+            story: short_id,
+            title,
+            description,
+        } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let story = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "edit_story.story",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&short_id[..]).unwrap(),),
+            )
+            .await?;
+        let story = match story {
+            Some(story) => story,
+            None => return Ok((c, false)),
+        };
+
+        if story.get::<u32, _>("user_id").unwrap() != user {
+            return Ok((c, false));
+        }
+        let story = story.get::<u32, _>("id").unwrap();
+
+        // the edit form
+        c.exec_drop(
+            q.get(
+                "edit_story.taggings",
+                "SELECT `taggings`.* FROM `taggings` \
+                 WHERE `taggings`.`story_id` = ?",
+            ),
+            (story,),
+        )
+        .await?;
+
+        c.query_drop(q.get(
+            "edit_story.tags",
+            "SELECT `tags`.* FROM `tags` \
+             WHERE `tags`.`inactive` = 0 \
+             ORDER BY `tags`.`tag` ASC",
+        ))
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "edit_story.update",
+                "UPDATE `stories` \
+                 SET `stories`.`title` = ?, \
+                 `stories`.`description` = ?, \
+                 `stories`.`markeddown_description` = ? \
+                 WHERE `stories`.`id` = ?",
+            ),
+            (
+                &title,
+                &description,
+                format!("<p>{}</p>\n", description),
+                story,
+            ),
+        )
+        .await?;
+
+        Story {
+            acting_as,
+            id: short_id,
+        }
+        .handle(future::ready(Ok(c)), q)
+        .await
+    }
+}
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use chrono::Duration;
+use metrics::counter;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error};
+use std::future::Future;
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// Periodic maintenance that takes stories older than `max_age` off the listings.
+pub(crate) struct ExpireStories {
+    pub(crate) max_age: Duration,
+}
+
+#[async_trait]
+impl Page for ExpireStories {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let ExpireStories { max_age } = self;
+        let mut c = c.await?;
+
+        let cutoff = chrono::Local::now().naive_local() - max_age;
+        let expired = c
+            .exec_iter(
+                q.get(
+                    "expire_stories.expire",
+                    "UPDATE `stories` \
+                     SET `stories`.`is_expired` = 1 \
+                     WHERE `stories`.`is_expired` = 0 \
+                     AND `stories`.`created_at` < ?",
+                ),
+                (cutoff,),
+            )
+            .await?;
+        counter!("lobsters_expired_stories").increment(expired.affected_rows());
+        expired.drop_result().await?;
+
+        Ok((c, false))
+    }
+}
@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::HashSet;
+use std::future::{self, Future};
+use trawler::UserId;
+
+use super::frontpage::Frontpage;
+use super::recent::Recent;
+use super::{listing, Page};
+use crate::queries::QueryCatalog;
+
+/// Which listing a feed is for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum FeedOf {
+    /// `/rss` and `/hottest.json`.
+    Frontpage,
+    /// `/newest.rss` and `/newest.json`.
+    Newest,
+    /// `/~<user>/stories.rss` and `/~<user>/stories.json`.
+    User(UserId),
+}
+
+/// A feed reader polling one of the site's feeds. Feeds are always fetched anonymously, and
+/// the `.rss` and `.json` flavors of a feed issue the same queries: the ones of the listing,
+/// plus the descriptions and URLs of the listed stories that go into the items.
+pub(crate) struct Feed {
+    pub(crate) of: FeedOf,
+}
+
+#[async_trait]
+impl Page for Feed {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Feed { of } = self;
+        let c = c.await?;
+
+        let c = match of {
+            FeedOf::Frontpage => {
+                let (mut c, _) = Frontpage {
+                    acting_as: None,
+                    page: 0,
+                }
+                .handle(future::ready(Ok(c)), q)
+                .await?;
+                c.query_drop(q.get(
+                    "feed.frontpage_items",
+                    "SELECT `stories`.`id`, `stories`.`short_id`, `stories`.`url`, \
+                     `stories`.`title`, `stories`.`markeddown_description`, \
+                     `stories`.`created_at`, `stories`.`comments_count` \
+                     FROM `stories` \
+                     WHERE `stories`.`merged_story_id` IS NULL \
+                     AND `stories`.`is_expired` = 0 \
+                     ORDER BY hotness LIMIT 25",
+                ))
+                .await?;
+                c
+            }
+            FeedOf::Newest => {
+                let (mut c, _) = Recent {
+                    acting_as: None,
+                    page: 0,
+                }
+                .handle(future::ready(Ok(c)), q)
+                .await?;
+                c.query_drop(q.get(
+                    "feed.newest_items",
+                    "SELECT `stories`.`id`, `stories`.`short_id`, `stories`.`url`, \
+                     `stories`.`title`, `stories`.`markeddown_description`, \
+                     `stories`.`created_at`, `stories`.`comments_count` \
+                     FROM `stories` \
+                     WHERE `stories`.`merged_story_id` IS NULL \
+                     AND `stories`.`is_expired` = 0 \
+                     ORDER BY `stories`.`id` DESC LIMIT 25",
+                ))
+                .await?;
+                c
+            }
+            FeedOf::User(uid) => user_feed(c, q, uid).await?,
+        };
+
+        Ok((c, false))
+    }
+}
+
+/// The stories a user submitted most recently, with their tags.
+async fn user_feed(mut c: Conn, q: &QueryCatalog, uid: UserId) -> Result<Conn, Error> {
+    if !super::user_exists(&mut c, q, "feed.user", uid).await? {
+        return Ok(c);
+    }
+
+    let stories = c
+        .exec_iter(
+            q.get(
+                "feed.user_items",
+                "SELECT `stories`.* \
+                 FROM `stories` \
+                 WHERE `stories`.`user_id` = ? \
+                 AND `stories`.`merged_story_id` IS NULL \
+                 AND `stories`.`is_expired` = 0 \
+                 ORDER BY `stories`.`id` DESC LIMIT 25",
+            ),
+            (uid,),
+        )
+        .await?
+        .reduce_and_drop(HashSet::new(), |mut stories, story: Row| {
+            stories.insert(story.get::<u32, _>("id").unwrap());
+            stories
+        })
+        .await?;
+    if stories.is_empty() {
+        return Ok(c);
+    }
+
+    c = listing::tags(c, q, "feed", &stories).await?;
+
+    Ok(c)
+}
@@ -0,0 +1,87 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::{StoryId, UserId};
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user unfollowing a story, so replies to them there no longer show up in their
+/// notifications, or following it again if they had unfollowed it.
+pub(crate) struct Follow {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) story: StoryId,
+}
+
+#[async_trait]
+impl Page for Follow {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Follow { acting_as, story } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let story = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "follow.story",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&story[..]).unwrap(),),
+            )
+            .await?
+            .unwrap();
+        let story = story.get::<u32, _>("id").unwrap();
+
+        let rr = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "follow.read_ribbon",
+                    "SELECT  `read_ribbons`.* \
+                     FROM `read_ribbons` \
+                     WHERE `read_ribbons`.`user_id` = ? \
+                     AND `read_ribbons`.`story_id` = ?",
+                ),
+                (user, story),
+            )
+            .await?;
+
+        let now = chrono::Local::now().naive_local();
+        match rr {
+            // stories are followed until they are unfollowed
+            None => {
+                c.exec_drop(
+                    q.get(
+                        "follow.insert_read_ribbon",
+                        "INSERT INTO `read_ribbons` \
+                         (`is_following`, `created_at`, `updated_at`, `user_id`, `story_id`) \
+                         VALUES (0, ?, ?, ?, ?)",
+                    ),
+                    (now, now, user, story),
+                )
+                .await?;
+            }
+            Some(rr) => {
+                let following = rr.get::<bool, _>("is_following").unwrap();
+                c.exec_drop(
+                    q.get(
+                        "follow.update_read_ribbon",
+                        "UPDATE `read_ribbons` \
+                         SET `read_ribbons`.`is_following` = ?, \
+                         `read_ribbons`.`updated_at` = ? \
+                         WHERE `read_ribbons`.`id` = ?",
+                    ),
+                    (!following, now, rr.get::<u64, _>("id").unwrap()),
+                )
+                .await?;
+            }
+        }
+
+        Ok((c, true))
+    }
+}
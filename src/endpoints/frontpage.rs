@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::HashSet;
+use std::future::Future;
+use trawler::UserId;
+
+use super::listing::ids_in;
+use super::{listing, Page, STORIES_PER_PAGE};
+use crate::queries::QueryCatalog;
+
+pub(crate) struct Frontpage {
+    pub(crate) acting_as: Option<UserId>,
+    /// Which page of the listing, starting at 0 for the first.
+    pub(crate) page: u32,
+}
+
+#[async_trait]
+impl Page for Frontpage {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Frontpage { acting_as, page } = self;
+        let mut c = c.await?;
+        let stories = c
+            .exec_iter(
+                q.variant("frontpage.stories"),
+                (STORIES_PER_PAGE + 1, page * STORIES_PER_PAGE),
+            )
+            .await?;
+        let (mut users, stories) = stories
+            .reduce_and_drop(
+                (HashSet::new(), HashSet::new()),
+                |(mut users, mut stories), story: Row| {
+                    users.insert(story.get::<u32, _>("user_id").unwrap());
+                    stories.insert(story.get::<u32, _>("id").unwrap());
+                    (users, stories)
+                },
+            )
+            .await?;
+
+        // pages past the end of the listing are empty, but the first page never is
+        if stories.is_empty() {
+            assert!(page > 0, "got no stories from /frontpage");
+            return Ok((c, true));
+        }
+
+        let stories_in = ids_in(&stories);
+
+        // listed stories also show the stories that were merged into them
+        let merged_authors = c
+            .query_iter(
+                q.get(
+                    "frontpage.merged_stories",
+                    "SELECT `stories`.`id`, `stories`.`user_id` \
+                     FROM `stories` \
+                     WHERE `stories`.`merged_story_id` IN ({stories})",
+                )
+                .replace("{stories}", &stories_in),
+            )
+            .await?
+            .reduce_and_drop(Vec::new(), |mut authors, story: Row| {
+                authors.push(story.get::<u32, _>("user_id").unwrap());
+                authors
+            })
+            .await?;
+        users.extend(merged_authors);
+
+        if let Some(uid) = acting_as {
+            c = listing::filters(c, q, "frontpage", uid, &stories).await?;
+        }
+        c = listing::stories(c, q, "frontpage", acting_as, &users, &stories).await?;
+
+        Ok((c, true))
+    }
+}
@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::UserId;
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A moderator going through the pending hat requests and granting the oldest one, which
+/// also lets the requester know by private message.
+pub(crate) struct GrantHat {
+    pub(crate) acting_as: Option<UserId>,
+    /// Short id of the message sent to the requester.
+    pub(crate) short_id: String,
+}
+
+#[async_trait]
+impl Page for GrantHat {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let GrantHat {
+            acting_as,
+            short_id,
+        } = self;
+        let mut c = c.await?;
+        let moderator = acting_as.unwrap();
+
+        let request = c
+            .query_first::<Row, _>(q.get(
+                "grant_hat.oldest_request",
+                "SELECT `hat_requests`.* \
+                 FROM `hat_requests` \
+                 ORDER BY `hat_requests`.`id` ASC \
+                 LIMIT 1",
+            ))
+            .await?;
+        let request = match request {
+            Some(request) => request,
+            None => return Ok((c, true)),
+        };
+
+        let id = request.get::<u32, _>("id").unwrap();
+        let user = request.get::<u32, _>("user_id").unwrap();
+        let hat = request.get::<String, _>("hat").unwrap();
+        let link = request.get::<Option<String>, _>("link").unwrap();
+
+        c.exec_drop(
+            q.get(
+                "grant_hat.requester",
+                "SELECT `users`.* FROM `users` WHERE `users`.`id` = ?",
+            ),
+            (user,),
+        )
+        .await?;
+
+        let now = chrono::Local::now().naive_local();
+        c.exec_drop(
+            q.get(
+                "grant_hat.insert_hat",
+                "INSERT INTO `hats` \
+                 (`created_at`, `updated_at`, `user_id`, `granted_by_user_id`, `hat`, `link`) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            ),
+            (now, now, user, moderator, &hat, link),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "grant_hat.insert_message",
+                "INSERT INTO `messages` \
+                 (`created_at`, `author_user_id`, `recipient_user_id`, \
+                 `subject`, `body`, `short_id`) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            ),
+            (
+                now,
+                moderator,
+                user,
+                format!("Your hat \"{}\" has been approved", hat),
+                "This hat may now be worn when commenting.",
+                short_id,
+            ),
+        )
+        .await?;
+        let mut c = super::update_unread_messages(c, q, user).await?;
+
+        c.exec_drop(
+            q.get(
+                "grant_hat.delete_request",
+                "DELETE FROM `hat_requests` WHERE `hat_requests`.`id` = ?",
+            ),
+            (id,),
+        )
+        .await?;
+
+        Ok((c, true))
+    }
+}
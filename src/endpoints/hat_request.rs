@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error};
+use std::future::Future;
+use trawler::UserId;
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user asking the moderators for a hat, to wear when speaking for a project or an
+/// employer.
+pub(crate) struct HatRequest {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) hat: String,
+    pub(crate) comment: String,
+}
+
+#[async_trait]
+impl Page for HatRequest {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let HatRequest {
+            acting_as,
+            hat,
+            comment,
+        } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let now = chrono::Local::now().naive_local();
+        c.exec_drop(
+            q.get(
+                "hat_request.insert",
+                "INSERT INTO `hat_requests` \
+                 (`created_at`, `updated_at`, `user_id`, `hat`, `link`, `comment`) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            ),
+            (
+                now,
+                now,
+                user,
+                &hat,
+                format!("https://example.com/~user{}", user),
+                comment,
+            ),
+        )
+        .await?;
+
+        Ok((c, true))
+    }
+}
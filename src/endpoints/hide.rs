@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::{StoryId, UserId};
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user hiding a story from their listings, or unhiding it if they had already hidden it.
+pub(crate) struct Hide {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) story: StoryId,
+}
+
+#[async_trait]
+impl Page for Hide {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Hide { acting_as, story } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let story = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "hide.story",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&story[..]).unwrap(),),
+            )
+            .await?
+            .unwrap();
+        let story = story.get::<u32, _>("id").unwrap();
+
+        let hidden = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "hide.hidden",
+                    "SELECT `hidden_stories`.* \
+                     FROM `hidden_stories` \
+                     WHERE `hidden_stories`.`user_id` = ? \
+                     AND `hidden_stories`.`story_id` = ?",
+                ),
+                (user, story),
+            )
+            .await?;
+
+        match hidden {
+            Some(hidden) => {
+                c.exec_drop(
+                    q.get(
+                        "hide.unhide",
+                        "DELETE FROM `hidden_stories` \
+                         WHERE `hidden_stories`.`id` = ?",
+                    ),
+                    (hidden.get::<u32, _>("id").unwrap(),),
+                )
+                .await?;
+            }
+            None => {
+                c.exec_drop(
+                    q.get(
+                        "hide.hide",
+                        "INSERT INTO `hidden_stories` (`user_id`, `story_id`) \
+                         VALUES (?, ?)",
+                    ),
+                    (user, story),
+                )
+                .await?;
+            }
+        }
+
+        Ok((c, false))
+    }
+}
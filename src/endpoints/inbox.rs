@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::HashSet;
+use std::future::Future;
+use trawler::UserId;
+
+use super::listing::ids_in;
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user listing the private messages they received.
+pub(crate) struct Inbox {
+    pub(crate) acting_as: Option<UserId>,
+}
+
+#[async_trait]
+impl Page for Inbox {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Inbox { acting_as } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let authors = c
+            .exec_iter(
+                q.get(
+                    "inbox.messages",
+                    "SELECT `messages`.* \
+                     FROM `messages` \
+                     WHERE `messages`.`recipient_user_id` = ? \
+                     AND `messages`.`deleted_by_recipient` = 0 \
+                     ORDER BY `messages`.`id` DESC",
+                ),
+                (user,),
+            )
+            .await?
+            .reduce_and_drop(HashSet::new(), |mut authors, message: Row| {
+                authors.insert(message.get::<u32, _>("author_user_id").unwrap());
+                authors
+            })
+            .await?;
+
+        if !authors.is_empty() {
+            let authors = ids_in(&authors);
+            c.query_drop(
+                q.get(
+                    "inbox.authors",
+                    "SELECT `users`.* FROM `users` \
+                     WHERE `users`.`id` IN ({users})",
+                )
+                .replace("{users}", &authors),
+            )
+            .await?;
+        }
+
+        Ok((c, true))
+    }
+}
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error};
+use std::future::Future;
+use trawler::UserId;
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A visitor asking to be invited, and then confirming their address from the email the real
+/// site sends them, which puts them on the list of requests that users can invite from.
+pub(crate) struct InvitationRequest {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) code: String,
+    pub(crate) name: String,
+    pub(crate) memo: String,
+}
+
+#[async_trait]
+impl Page for InvitationRequest {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let InvitationRequest {
+            acting_as: _,
+            code,
+            name,
+            memo,
+        } = self;
+        let mut c = c.await?;
+
+        let now = chrono::Local::now().naive_local();
+        c.exec_drop(
+            q.get(
+                "invitation_request.insert",
+                "INSERT INTO `invitation_requests` \
+                 (`code`, `email`, `name`, `memo`, `ip_address`, `created_at`, `updated_at`) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            ),
+            (
+                &code,
+                format!("{}@example.com", name),
+                &name,
+                memo,
+                "127.0.0.1",
+                now,
+                now,
+            ),
+        )
+        .await?;
+
+        let request = c
+            .exec_first::<u32, _, _>(
+                q.get(
+                    "invitation_request.request",
+                    "SELECT `invitation_requests`.`id` \
+                     FROM `invitation_requests` \
+                     WHERE `invitation_requests`.`code` = ?",
+                ),
+                (&code,),
+            )
+            .await?;
+        if let Some(request) = request {
+            c.exec_drop(
+                q.get(
+                    "invitation_request.verify",
+                    "UPDATE `invitation_requests` \
+                     SET `invitation_requests`.`is_verified` = 1, \
+                     `invitation_requests`.`updated_at` = ? \
+                     WHERE `invitation_requests`.`id` = ?",
+                ),
+                (now, request),
+            )
+            .await?;
+        }
+
+        Ok((c, false))
+    }
+}
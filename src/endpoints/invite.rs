@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::UserId;
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user going through the verified invitation requests and inviting the oldest one. With no
+/// requests pending, they invite someone they know instead.
+pub(crate) struct Invite {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) code: String,
+}
+
+#[async_trait]
+impl Page for Invite {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Invite { acting_as, code } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let request = c
+            .query_first::<Row, _>(q.get(
+                "invite.oldest_request",
+                "SELECT `invitation_requests`.* \
+                 FROM `invitation_requests` \
+                 WHERE `invitation_requests`.`is_verified` = 1 \
+                 ORDER BY `invitation_requests`.`id` ASC \
+                 LIMIT 1",
+            ))
+            .await?;
+
+        let (email, memo) = match request {
+            Some(request) => {
+                c.exec_drop(
+                    q.get(
+                        "invite.delete_request",
+                        "DELETE FROM `invitation_requests` \
+                         WHERE `invitation_requests`.`id` = ?",
+                    ),
+                    (request.get::<u32, _>("id").unwrap(),),
+                )
+                .await?;
+                (
+                    request.get::<String, _>("email").unwrap(),
+                    request.get::<Option<String>, _>("memo").unwrap(),
+                )
+            }
+            None => (format!("{}@example.com", code), None),
+        };
+
+        let now = chrono::Local::now().naive_local();
+        c.exec_drop(
+            q.get(
+                "invite.insert",
+                "INSERT INTO `invitations` \
+                 (`user_id`, `email`, `code`, `created_at`, `updated_at`, `memo`) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            ),
+            (user, email, code, now, now, memo),
+        )
+        .await?;
+
+        Ok((c, true))
+    }
+}
@@ -0,0 +1,206 @@
+// The queries the story listings (the frontpage, `/recent`, tag pages and feeds) share. Each
+// listing passes its name as `page` and keeps its own query names (e.g. `frontpage.users` and
+// `tag.users`), so they can be told apart and overridden separately.
+
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::iter;
+use trawler::UserId;
+
+use crate::queries::QueryCatalog;
+
+/// Comma-separated `ids`, for filling in an `IN ({...})` placeholder.
+pub(super) fn ids_in<'a, T: Display + 'a>(ids: impl IntoIterator<Item = &'a T>) -> String {
+    ids.into_iter()
+        .map(|id| format!("{}", id))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The stories a logged-in user hid, and the taggings of the listed `stories` that their tag
+/// filters apply to.
+pub(crate) async fn filters(
+    mut c: Conn,
+    q: &QueryCatalog,
+    page: &str,
+    uid: UserId,
+    stories: &HashSet<u32>,
+) -> Result<Conn, Error> {
+    c.exec_drop(
+        q.get(
+            &format!("{}.hidden_stories", page),
+            "SELECT `hidden_stories`.`story_id` \
+             FROM `hidden_stories` \
+             WHERE `hidden_stories`.`user_id` = ?",
+        ),
+        (uid,),
+    )
+    .await?;
+
+    let tags = c
+        .exec_iter(
+            q.get(
+                &format!("{}.tag_filters", page),
+                "SELECT `tag_filters`.* FROM `tag_filters` \
+                 WHERE `tag_filters`.`user_id` = ?",
+            ),
+            (uid,),
+        )
+        .await?
+        .reduce_and_drop(Vec::new(), |mut tags, tag: Row| {
+            tags.push(tag.get::<u32, _>("tag_id").unwrap());
+            tags
+        })
+        .await?;
+
+    // variants that filter in the query need the user's filtered tags, the others fetch the
+    // taggings of all the listed stories whether the user filters or not
+    let filtered = q.variant(&format!("{}.filtered_taggings", page));
+    if !tags.is_empty() || !filtered.contains("{tags}") {
+        c.query_drop(
+            filtered
+                .replace("{stories}", &ids_in(stories))
+                .replace("{tags}", &ids_in(&tags)),
+        )
+        .await?;
+    }
+
+    Ok(c)
+}
+
+/// Everything that is shown along with the listed `stories`: their submitters (`users`),
+/// suggestions, and tags, plus which of them a logged-in user voted on, hid, or saved.
+pub(crate) async fn stories(
+    mut c: Conn,
+    q: &QueryCatalog,
+    page: &str,
+    acting_as: Option<UserId>,
+    users: &HashSet<u32>,
+    stories: &HashSet<u32>,
+) -> Result<Conn, Error> {
+    let stories_in = ids_in(stories);
+
+    c.query_drop(
+        q.get(
+            &format!("{}.users", page),
+            "SELECT `users`.* FROM `users` WHERE `users`.`id` IN ({users})",
+        )
+        .replace("{users}", &ids_in(users)),
+    )
+    .await?;
+
+    c.query_drop(
+        q.get(
+            &format!("{}.suggested_titles", page),
+            "SELECT `suggested_titles`.* \
+             FROM `suggested_titles` \
+             WHERE `suggested_titles`.`story_id` IN ({stories})",
+        )
+        .replace("{stories}", &stories_in),
+    )
+    .await?;
+
+    c.query_drop(
+        q.get(
+            &format!("{}.suggested_taggings", page),
+            "SELECT `suggested_taggings`.* \
+             FROM `suggested_taggings` \
+             WHERE `suggested_taggings`.`story_id` IN ({stories})",
+        )
+        .replace("{stories}", &stories_in),
+    )
+    .await?;
+
+    c = tags(c, q, page, stories).await?;
+
+    // also load things that we need to highlight
+    if let Some(uid) = acting_as {
+        let story_params = stories.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let values: Vec<_> = iter::once(&uid as &_)
+            .chain(stories.iter().map(|s| s as &_))
+            .collect();
+        c.exec_drop(
+            q.get(
+                &format!("{}.votes", page),
+                "SELECT `votes`.* FROM `votes` \
+                 WHERE `votes`.`user_id` = ? \
+                 AND `votes`.`story_id` IN ({stories}) \
+                 AND `votes`.`comment_id` IS NULL",
+            )
+            .replace("{stories}", &story_params),
+            values,
+        )
+        .await?;
+
+        let values: Vec<_> = iter::once(&uid as &_)
+            .chain(stories.iter().map(|s| s as &_))
+            .collect();
+        c.exec_drop(
+            q.get(
+                &format!("{}.hidden", page),
+                "SELECT `hidden_stories`.* \
+                 FROM `hidden_stories` \
+                 WHERE `hidden_stories`.`user_id` = ? \
+                 AND `hidden_stories`.`story_id` IN ({stories})",
+            )
+            .replace("{stories}", &story_params),
+            values,
+        )
+        .await?;
+
+        let values: Vec<_> = iter::once(&uid as &_)
+            .chain(stories.iter().map(|s| s as &_))
+            .collect();
+        c.exec_drop(
+            q.get(
+                &format!("{}.saved", page),
+                "SELECT `saved_stories`.* \
+                 FROM `saved_stories` \
+                 WHERE `saved_stories`.`user_id` = ? \
+                 AND `saved_stories`.`story_id` IN ({stories})",
+            )
+            .replace("{stories}", &story_params),
+            values,
+        )
+        .await?;
+    }
+
+    Ok(c)
+}
+
+/// The tags of the listed `stories`.
+pub(crate) async fn tags(
+    mut c: Conn,
+    q: &QueryCatalog,
+    page: &str,
+    stories: &HashSet<u32>,
+) -> Result<Conn, Error> {
+    let tags = c
+        .query_iter(
+            q.get(
+                &format!("{}.taggings", page),
+                "SELECT `taggings`.* FROM `taggings` \
+                 WHERE `taggings`.`story_id` IN ({stories})",
+            )
+            .replace("{stories}", &ids_in(stories)),
+        )
+        .await?
+        .reduce_and_drop(HashSet::new(), |mut tags, tagging: Row| {
+            tags.insert(tagging.get::<u32, _>("tag_id").unwrap());
+            tags
+        })
+        .await?;
+
+    c.query_drop(
+        q.get(
+            &format!("{}.tags", page),
+            "SELECT `tags`.* FROM `tags` WHERE `tags`.`id` IN ({tags})",
+        )
+        .replace("{tags}", &ids_in(&tags)),
+    )
+    .await?;
+
+    Ok(c)
+}
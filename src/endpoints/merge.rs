@@ -0,0 +1,255 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::{StoryId, UserId};
+
+use super::ranking::{self, Hotness};
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A moderator merging a story into another, recent one (e.g., a duplicate submission).
+pub(crate) struct Merge {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) story: StoryId,
+    /// How far back in the list of recent stories to look for the one to merge into.
+    pub(crate) target_offset: u32,
+}
+
+#[async_trait]
+impl Page for Merge {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Merge {
+            acting_as,
+            story,
+            target_offset,
+        } = self;
+        let mut c = c.await?;
+        let moderator = acting_as.unwrap();
+
+        let story = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "merge.story",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&story[..]).unwrap(),),
+            )
+            .await?
+            .unwrap();
+        if story
+            .get::<Option<u32>, _>("merged_story_id")
+            .unwrap()
+            .is_some()
+        {
+            // already merged, nothing for the moderator to do
+            return Ok((c, false));
+        }
+        let story_row = story;
+        let story = story_row.get::<u32, _>("id").unwrap();
+
+        let target = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "merge.target",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`merged_story_id` IS NULL \
+                     AND `stories`.`is_expired` = 0 \
+                     AND `stories`.`id` <> ? \
+                     ORDER BY `stories`.`id` DESC \
+                     LIMIT 1 OFFSET ?",
+                ),
+                (story, target_offset),
+            )
+            .await?;
+        let target = match target {
+            Some(target) => target,
+            None => return Ok((c, false)),
+        };
+        let target_row = target;
+        let target_short_id = target_row.get::<String, _>("short_id").unwrap();
+        let target = target_row.get::<u32, _>("id").unwrap();
+
+        c.exec_drop(
+            q.get(
+                "merge.update_story",
+                "UPDATE `stories` \
+                 SET `stories`.`merged_story_id` = ? \
+                 WHERE `stories`.`id` = ?",
+            ),
+            (target, story),
+        )
+        .await?;
+
+        // don't leave chains of merges behind
+        c.exec_drop(
+            q.get(
+                "merge.update_merged_stories",
+                "UPDATE `stories` \
+                 SET `stories`.`merged_story_id` = ? \
+                 WHERE `stories`.`merged_story_id` = ?",
+            ),
+            (target, story),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "merge.move_comments",
+                "UPDATE `comments` \
+                 SET `comments`.`story_id` = ? \
+                 WHERE `comments`.`story_id` = ?",
+            ),
+            (target, story),
+        )
+        .await?;
+
+        // votes on the moved comments are looked up by the story they are on
+        c.exec_drop(
+            q.get(
+                "merge.move_comment_votes",
+                "UPDATE `votes` \
+                 SET `votes`.`story_id` = ? \
+                 WHERE `votes`.`story_id` = ? \
+                 AND `votes`.`comment_id` IS NOT NULL",
+            ),
+            (target, story),
+        )
+        .await?;
+
+        let count = c
+            .exec_iter(
+                q.get(
+                    "merge.target_comments",
+                    "SELECT `comments`.`id` \
+                     FROM `comments` \
+                     WHERE `comments`.`story_id` = ?",
+                ),
+                (target,),
+            )
+            .await?
+            .reduce_and_drop(0, |rows, _: Row| rows + 1)
+            .await?;
+
+        c.exec_drop(
+            q.get(
+                "merge.update_comments_count",
+                "UPDATE `stories` \
+                 SET `comments_count` = ? \
+                 WHERE `stories`.`id` = ?",
+            ),
+            (count, target),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "merge.clear_comments_count",
+                "UPDATE `stories` \
+                 SET `comments_count` = 0 \
+                 WHERE `stories`.`id` = ?",
+            ),
+            (story,),
+        )
+        .await?;
+
+        // the target now has the comments and votes of the merged story, which has neither
+        c = update_hotness(c, q, &target_row).await?;
+        c = update_hotness(c, q, &story_row).await?;
+
+        let now = chrono::Local::now().naive_local();
+        c.exec_drop(
+            q.get(
+                "merge.insert_moderation",
+                "INSERT INTO `moderations` \
+                 (`created_at`, `updated_at`, `moderator_user_id`, `story_id`, `action`) \
+                 VALUES (?, ?, ?, ?, ?)",
+            ),
+            (
+                now,
+                now,
+                moderator,
+                story,
+                format!("merged into {}", target_short_id),
+            ),
+        )
+        .await?;
+
+        Ok((c, false))
+    }
+}
+
+/// Recompute the hotness of the `stories` row `story` from its current tags, comments and
+/// merged stories.
+async fn update_hotness(mut c: Conn, q: &QueryCatalog, story: &Row) -> Result<Conn, Error> {
+    let mut hotness = Hotness::of(story);
+    let story = story.get::<u32, _>("id").unwrap();
+
+    hotness.tags_mod = c
+        .exec_iter(
+            q.get(
+                "merge.tags",
+                "SELECT `tags`.* \
+                 FROM `tags` \
+                 INNER JOIN `taggings` ON `tags`.`id` = `taggings`.`tag_id` \
+                 WHERE `taggings`.`story_id` = ?",
+            ),
+            (story,),
+        )
+        .await?
+        .reduce_and_drop(0.0, |tags_mod, tag: Row| {
+            tags_mod + ranking::hotness_mod(&tag)
+        })
+        .await?;
+
+    hotness.comment_votes = c
+        .exec_iter(
+            q.get(
+                "merge.comment_votes",
+                "SELECT \
+                 `comments`.`upvotes`, \
+                 `comments`.`downvotes` \
+                 FROM `comments` \
+                 JOIN `stories` ON (`stories`.`id` = `comments`.`story_id`) \
+                 WHERE `comments`.`story_id` = ? \
+                 AND `comments`.`user_id` <> `stories`.`user_id`",
+            ),
+            (story,),
+        )
+        .await?
+        .map_and_drop(|comment: Row| ranking::votes(&comment))
+        .await?;
+
+    hotness.merged_votes = c
+        .exec_iter(
+            q.get(
+                "merge.merged_votes",
+                "SELECT `stories`.`id`, `stories`.`upvotes`, `stories`.`downvotes` \
+                 FROM `stories` \
+                 WHERE `stories`.`merged_story_id` = ?",
+            ),
+            (story,),
+        )
+        .await?
+        .map_and_drop(|merged: Row| ranking::votes(&merged))
+        .await?;
+
+    c.exec_drop(
+        q.get(
+            "merge.update_hotness",
+            "UPDATE `stories` \
+             SET `hotness` = ? \
+             WHERE `stories`.`id` = ?",
+        ),
+        (hotness.compute(), story),
+    )
+    .await?;
+
+    Ok(c)
+}
@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error};
+use std::future::Future;
+use trawler::UserId;
+
+use super::Page;
+use crate::lorem;
+use crate::queries::QueryCatalog;
+
+/// A user sending a private message to another user.
+pub(crate) struct Message {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) recipient: UserId,
+    pub(crate) short_id: String,
+}
+
+#[async_trait]
+impl Page for Message {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Message {
+            acting_as,
+            recipient,
+            short_id,
+        } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+        let (subject, body) = {
+            let mut rng = rand::thread_rng();
+            (lorem::terms(&mut rng), lorem::paragraph(&mut rng))
+        };
+
+        if !super::user_exists(&mut c, q, "message.recipient", recipient).await? {
+            return Ok((c, true));
+        }
+
+        c.exec_drop(
+            q.get(
+                "message.insert",
+                "INSERT INTO `messages` \
+                 (`created_at`, `author_user_id`, `recipient_user_id`, \
+                 `subject`, `body`, `short_id`) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            ),
+            (
+                chrono::Local::now().naive_local(),
+                user,
+                recipient,
+                subject,
+                body,
+                short_id,
+            ),
+        )
+        .await?;
+
+        // the recipient sees the new message in their notifications on their next page view
+        let c = super::update_unread_messages(c, q, recipient).await?;
+
+        Ok((c, true))
+    }
+}
@@ -1,3 +1,207 @@
 //pub(crate) mod natural;
 pub(crate) mod noria;
 pub(crate) mod original;
+
+pub(crate) mod comment;
+pub(crate) mod comment_vote;
+pub(crate) mod comments;
+pub(crate) mod delete_comment;
+pub(crate) mod doff_hat;
+pub(crate) mod edit_comment;
+pub(crate) mod edit_story;
+pub(crate) mod expire_stories;
+pub(crate) mod feed;
+pub(crate) mod follow;
+pub(crate) mod frontpage;
+pub(crate) mod grant_hat;
+pub(crate) mod hat_request;
+pub(crate) mod hide;
+pub(crate) mod inbox;
+pub(crate) mod invitation_request;
+pub(crate) mod invite;
+pub(crate) mod listing;
+pub(crate) mod merge;
+pub(crate) mod message;
+pub(crate) mod moderate;
+pub(crate) mod moderations;
+pub(crate) mod preferences;
+pub(crate) mod ranking;
+pub(crate) mod read_message;
+pub(crate) mod recent;
+pub(crate) mod replies;
+pub(crate) mod save;
+pub(crate) mod search;
+pub(crate) mod signup;
+pub(crate) mod story;
+pub(crate) mod story_vote;
+pub(crate) mod submit;
+pub(crate) mod suggest_tags;
+pub(crate) mod suggest_title;
+pub(crate) mod tag;
+pub(crate) mod tag_filters;
+pub(crate) mod threads;
+pub(crate) mod user;
+pub(crate) mod user_tree;
+pub(crate) mod votes;
+
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use std::future::Future;
+use trawler::UserId;
+
+use crate::queries::QueryCatalog;
+
+/// A lobsters page (or action), issuing the same queries the real site does to render it.
+///
+/// The control flow of a page is shared by all variants; the SQL for the steps where the
+/// variants differ is looked up in the [`QueryCatalog`], as supplied by a [`QueryProvider`].
+#[async_trait]
+pub(crate) trait Page: Send {
+    /// Run the page, returning the connection and whether the notifications for the acting
+    /// user should be loaded, too.
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send;
+}
+
+/// The SQL a variant issues for the steps where it differs from the others.
+///
+/// Every provider must supply the queries the pages look up with [`QueryCatalog::variant`]
+/// (`notifications.replies`, `replies.replies`, `frontpage.stories`,
+/// `frontpage.filtered_taggings`, `recent.stories`, `recent.filtered_taggings`, `tag.stories`,
+/// `user.top_tag` and `story.comments`), and may supply the ones looked up with [`QueryCatalog::optional`].
+///
+/// The `*.filtered_taggings` queries only run for users that filter tags if they have a
+/// `{tags}` placeholder for the filtered tags, and for every logged-in user otherwise.
+pub(crate) trait QueryProvider {
+    /// The `(name, sql)` pairs for this variant.
+    fn queries(&self) -> &'static [(&'static str, &'static str)];
+}
+
+pub(crate) async fn notifications(mut c: Conn, q: &QueryCatalog, uid: u32) -> Result<Conn, Error> {
+    c.exec_drop(q.variant("notifications.replies"), (uid,))
+        .await?;
+
+    c.exec_drop(
+        q.get(
+            "notifications.unread_messages",
+            "SELECT `keystores`.* \
+             FROM `keystores` \
+             WHERE `keystores`.`key` = ?",
+        ),
+        (format!("user:{}:unread_messages", uid),),
+    )
+    .await?;
+
+    Ok(c)
+}
+
+/// Recount the unread messages of `uid` into the keystore that [`notifications`] reads.
+pub(crate) async fn update_unread_messages(
+    mut c: Conn,
+    q: &QueryCatalog,
+    uid: u32,
+) -> Result<Conn, Error> {
+    let unread = c
+        .exec_first::<u64, _, _>(
+            q.get(
+                "messages.count_unread",
+                "SELECT COUNT(*) FROM `messages` \
+                 WHERE `messages`.`recipient_user_id` = ? \
+                 AND `messages`.`has_been_read` = 0 \
+                 AND `messages`.`deleted_by_recipient` = 0",
+            ),
+            (uid,),
+        )
+        .await?
+        .unwrap_or(0);
+
+    c.exec_drop(
+        q.get(
+            "messages.update_unread",
+            "INSERT INTO `keystores` (`key`, `value`) \
+             VALUES (?, ?) \
+             ON DUPLICATE KEY UPDATE `keystores`.`value` = ?",
+        ),
+        (format!("user:{}:unread_messages", uid), unread, unread),
+    )
+    .await?;
+
+    Ok(c)
+}
+
+/// Look up the account of `uid` by its `user{uid}` username with the query `name`, returning
+/// whether it exists. Everything else refers to the user by trawler's id, so the row itself
+/// is never needed.
+pub(crate) async fn user_exists(
+    c: &mut Conn,
+    q: &QueryCatalog,
+    name: &str,
+    uid: UserId,
+) -> Result<bool, Error> {
+    let user = c
+        .exec_first::<Row, _, _>(
+            q.get(
+                name,
+                "SELECT  `users`.* FROM `users` \
+                 WHERE `users`.`username` = ?",
+            ),
+            (format!("user{}", uid),),
+        )
+        .await?;
+    Ok(user.is_some())
+}
+
+/// The secrets the real site generates for every new account.
+pub(crate) struct Credentials {
+    pub(crate) password_digest: String,
+    pub(crate) session_token: String,
+    pub(crate) rss_token: String,
+    pub(crate) mailing_list_token: String,
+}
+
+impl Credentials {
+    pub(crate) fn generate<R: Rng>(rng: &mut R) -> Self {
+        Self {
+            // shaped like a bcrypt digest
+            password_digest: format!("$2a$10${}", token(rng, 53)),
+            session_token: token(rng, 60),
+            rss_token: token(rng, 60),
+            mailing_list_token: token(rng, 10),
+        }
+    }
+}
+
+// Pages identify users by trawler's `UserId` throughout: it is stored as is in the user
+// columns of `stories`, `comments`, `votes` and `messages`, and the account created for it
+// is named `user{uid}`. Pages that look a user up by that name do so only to check that the
+// account exists; they keep using the trawler id for everything else.
+
+/// How many users are moderators: the ones with the lowest ids, whose accounts are created
+/// with the moderator flag set.
+pub(crate) const MODERATORS: UserId = 10;
+
+/// How many stories the frontpage, `/recent` and tag pages list per page. Their queries fetch one more
+/// story than that to tell whether there is a next page.
+pub(crate) const STORIES_PER_PAGE: u32 = 50;
+
+/// A page of a listing, starting at 0 for the first page. Each page is followed by the next
+/// with probability `next_page_rate`, so page numbers are geometrically distributed.
+pub(crate) fn listing_page<R: Rng>(rng: &mut R, next_page_rate: f64) -> u32 {
+    let mut page = 0;
+    while rng.gen::<f64>() < next_page_rate {
+        page += 1;
+    }
+    page
+}
+
+/// A random alphanumeric token of `len` characters, like the site's `Utils.random_str`.
+pub(crate) fn token<R: Rng>(rng: &mut R, len: usize) -> String {
+    rng.sample_iter(Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
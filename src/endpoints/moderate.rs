@@ -0,0 +1,212 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::{StoryId, UserId};
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// What a moderator does to a story or its discussion.
+#[derive(Clone, Debug)]
+pub(crate) enum Moderation {
+    /// Delete the most recent comment on the story that is still visible.
+    DeleteComment,
+    /// Take the story off the listings.
+    ExpireStory,
+    /// Give the story a new title.
+    EditTitle(String),
+}
+
+/// A moderator acting on a story they are looking at, leaving a public record in the
+/// moderation log.
+pub(crate) struct Moderate {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) story: StoryId,
+    pub(crate) action: Moderation,
+}
+
+#[async_trait]
+impl Page for Moderate {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Moderate {
+            acting_as,
+            story,
+            action,
+        } = self;
+        let mut c = c.await?;
+        let moderator = acting_as.unwrap();
+
+        let story = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "moderate.story",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&story[..]).unwrap(),),
+            )
+            .await?
+            .unwrap();
+        let author = story.get::<u32, _>("user_id").unwrap();
+        let is_expired = story.get::<bool, _>("is_expired").unwrap();
+        let old_title = story.get::<String, _>("title").unwrap();
+        let story = story.get::<u32, _>("id").unwrap();
+
+        let now = chrono::Local::now().naive_local();
+        match action {
+            Moderation::DeleteComment => {
+                let comment = c
+                    .exec_first::<Row, _, _>(
+                        q.get(
+                            "moderate.comment",
+                            "SELECT `comments`.* \
+                             FROM `comments` \
+                             WHERE `comments`.`story_id` = ? \
+                             AND `comments`.`is_deleted` = 0 \
+                             AND `comments`.`is_moderated` = 0 \
+                             ORDER BY `comments`.`id` DESC \
+                             LIMIT 1",
+                        ),
+                        (story,),
+                    )
+                    .await?;
+                let comment = match comment {
+                    Some(comment) => comment,
+                    None => return Ok((c, false)),
+                };
+                let commenter = comment.get::<u32, _>("user_id").unwrap();
+                let comment = comment.get::<u32, _>("id").unwrap();
+
+                c.exec_drop(
+                    q.get(
+                        "moderate.delete_comment",
+                        "UPDATE `comments` \
+                         SET `comments`.`is_deleted` = 1, \
+                         `comments`.`is_moderated` = 1, \
+                         `comments`.`updated_at` = ? \
+                         WHERE `comments`.`id` = ?",
+                    ),
+                    (now, comment),
+                )
+                .await?;
+
+                // the story only counts the comments that are still visible
+                let count = c
+                    .exec_first::<u64, _, _>(
+                        q.get(
+                            "moderate.active_comments",
+                            "SELECT COUNT(*) \
+                             FROM `comments` \
+                             WHERE `comments`.`story_id` = ? \
+                             AND `comments`.`is_deleted` = 0 \
+                             AND `comments`.`is_moderated` = 0",
+                        ),
+                        (story,),
+                    )
+                    .await?
+                    .unwrap_or(0);
+
+                c.exec_drop(
+                    q.get(
+                        "moderate.update_comments_count",
+                        "UPDATE `stories` \
+                         SET `comments_count` = ? \
+                         WHERE `stories`.`id` = ?",
+                    ),
+                    (count, story),
+                )
+                .await?;
+
+                c.exec_drop(
+                    q.get(
+                        "moderate.insert_comment_moderation",
+                        "INSERT INTO `moderations` \
+                         (`created_at`, `updated_at`, `moderator_user_id`, \
+                         `story_id`, `comment_id`, `user_id`, `action`, `reason`) \
+                         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                    ),
+                    (
+                        now,
+                        now,
+                        moderator,
+                        story,
+                        comment,
+                        commenter,
+                        "deleted comment",
+                        "off-topic",
+                    ),
+                )
+                .await?;
+            }
+            Moderation::ExpireStory => {
+                if is_expired {
+                    return Ok((c, false));
+                }
+
+                c.exec_drop(
+                    q.get(
+                        "moderate.expire_story",
+                        "UPDATE `stories` \
+                         SET `stories`.`is_expired` = 1, \
+                         `stories`.`is_moderated` = 1 \
+                         WHERE `stories`.`id` = ?",
+                    ),
+                    (story,),
+                )
+                .await?;
+
+                c.exec_drop(
+                    q.get(
+                        "moderate.insert_story_moderation",
+                        "INSERT INTO `moderations` \
+                         (`created_at`, `updated_at`, `moderator_user_id`, \
+                         `story_id`, `user_id`, `action`, `reason`) \
+                         VALUES (?, ?, ?, ?, ?, ?, ?)",
+                    ),
+                    (now, now, moderator, story, author, "deleted story", "spam"),
+                )
+                .await?;
+            }
+            Moderation::EditTitle(title) => {
+                c.exec_drop(
+                    q.get(
+                        "moderate.edit_title",
+                        "UPDATE `stories` \
+                         SET `stories`.`title` = ?, \
+                         `stories`.`is_moderated` = 1 \
+                         WHERE `stories`.`id` = ?",
+                    ),
+                    (&title, story),
+                )
+                .await?;
+
+                c.exec_drop(
+                    q.get(
+                        "moderate.insert_story_moderation",
+                        "INSERT INTO `moderations` \
+                         (`created_at`, `updated_at`, `moderator_user_id`, \
+                         `story_id`, `user_id`, `action`, `reason`) \
+                         VALUES (?, ?, ?, ?, ?, ?, ?)",
+                    ),
+                    (
+                        now,
+                        now,
+                        moderator,
+                        story,
+                        author,
+                        format!("changed title from {:?} to {:?}", old_title, title),
+                        "clarified title",
+                    ),
+                )
+                .await?;
+            }
+        }
+
+        Ok((c, false))
+    }
+}
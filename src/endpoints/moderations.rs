@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::HashSet;
+use std::future::Future;
+use trawler::UserId;
+
+use super::listing::ids_in;
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// The public `/moderations` log of the most recent moderator actions.
+pub(crate) struct Moderations {
+    pub(crate) acting_as: Option<UserId>,
+}
+
+#[async_trait]
+impl Page for Moderations {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Moderations { acting_as: _ } = self;
+        let mut c = c.await?;
+
+        let (users, stories, comments) = c
+            .query_iter(q.get(
+                "moderations.moderations",
+                "SELECT `moderations`.* \
+                 FROM `moderations` \
+                 ORDER BY `moderations`.`id` DESC \
+                 LIMIT 50 OFFSET 0",
+            ))
+            .await?
+            .reduce_and_drop(
+                (HashSet::new(), HashSet::new(), HashSet::new()),
+                |(mut users, mut stories, mut comments), moderation: Row| {
+                    users.extend(
+                        moderation
+                            .get::<Option<u32>, _>("moderator_user_id")
+                            .unwrap(),
+                    );
+                    users.extend(moderation.get::<Option<u32>, _>("user_id").unwrap());
+                    stories.extend(moderation.get::<Option<u32>, _>("story_id").unwrap());
+                    comments.extend(moderation.get::<Option<u32>, _>("comment_id").unwrap());
+                    (users, stories, comments)
+                },
+            )
+            .await?;
+
+        if !users.is_empty() {
+            let users = ids_in(&users);
+            c.query_drop(
+                q.get(
+                    "moderations.users",
+                    "SELECT `users`.* FROM `users` WHERE `users`.`id` IN ({users})",
+                )
+                .replace("{users}", &users),
+            )
+            .await?;
+        }
+
+        if !stories.is_empty() {
+            let stories = ids_in(&stories);
+            c.query_drop(
+                q.get(
+                    "moderations.stories",
+                    "SELECT `stories`.* FROM `stories` \
+                     WHERE `stories`.`id` IN ({stories})",
+                )
+                .replace("{stories}", &stories),
+            )
+            .await?;
+        }
+
+        if !comments.is_empty() {
+            let comments = ids_in(&comments);
+            c.query_drop(
+                q.get(
+                    "moderations.comments",
+                    "SELECT `comments`.* FROM `comments` \
+                     WHERE `comments`.`id` IN ({comments})",
+                )
+                .replace("{comments}", &comments),
+            )
+            .await?;
+        }
+
+        Ok((c, true))
+    }
+}
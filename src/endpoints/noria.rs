@@ -0,0 +1,95 @@
+use super::QueryProvider;
+
+/// Queries shaped for Noria/Readyset: computed values are selected as named columns so they
+/// can be filtered and ordered on, and notifications are read from a materialized view.
+pub(crate) struct Noria;
+
+impl QueryProvider for Noria {
+    fn queries(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            (
+                "notifications.replies",
+                "SELECT BOUNDARY_notifications.notifications
+                 FROM BOUNDARY_notifications
+                 WHERE BOUNDARY_notifications.user_id = ?",
+            ),
+            (
+                "replies.replies",
+                "SELECT `BOUNDARY_replying_comments_for_count`.* \
+                 FROM `BOUNDARY_replying_comments_for_count` \
+                 WHERE `BOUNDARY_replying_comments_for_count`.`user_id` = ? \
+                 ORDER BY `BOUNDARY_replying_comments_for_count`.`id` DESC \
+                 LIMIT 25",
+            ),
+            (
+                "frontpage.stories",
+                "SELECT  `stories`.*, CAST(`stories`.`upvotes` AS signed int) - \
+                 CAST(`stories`.`downvotes` AS signed int) AS `saldo` \
+                 FROM `stories` \
+                 WHERE `stories`.`merged_story_id` IS NULL \
+                 AND `stories`.`is_expired` = 0 \
+                 AND `saldo` >= 0 \
+                 ORDER BY hotness LIMIT ? OFFSET ?",
+            ),
+            (
+                "frontpage.filtered_taggings",
+                "SELECT `taggings`.`story_id` \
+                 FROM `taggings` \
+                 WHERE `taggings`.`story_id` IN ({stories})",
+            ),
+            (
+                "recent.stories",
+                "SELECT  `stories`.* \
+                 CAST(upvotes AS signed int) - CAST(downvotes AS signed int) AS saldo \
+                 FROM `stories` \
+                 WHERE `stories`.`merged_story_id` IS NULL \
+                 AND `stories`.`is_expired` = 0 \
+                 ORDER BY stories.id DESC LIMIT ? OFFSET ?",
+            ),
+            (
+                "recent.filtered_taggings",
+                "SELECT `taggings`.`story_id` \
+                 FROM `taggings` \
+                 WHERE `taggings`.`story_id` IN ({stories})",
+            ),
+            (
+                "tag.stories",
+                "SELECT  `stories`.*, CAST(`stories`.`upvotes` AS signed int) - \
+                 CAST(`stories`.`downvotes` AS signed int) AS `saldo` \
+                 FROM `stories` \
+                 INNER JOIN `taggings` ON `taggings`.`story_id` = `stories`.`id` \
+                 WHERE `stories`.`merged_story_id` IS NULL \
+                 AND `stories`.`is_expired` = 0 \
+                 AND `saldo` >= 0 \
+                 AND `taggings`.`tag_id` = ? \
+                 ORDER BY hotness LIMIT ? OFFSET ?",
+            ),
+            (
+                "user.top_tag",
+                "SELECT  `tags`.`id`, COUNT(*) AS `count` FROM `taggings` \
+                 INNER JOIN `tags` ON `taggings`.`tag_id` = `tags`.`id` \
+                 INNER JOIN `stories` ON `stories`.`id` = `taggings`.`story_id` \
+                 WHERE `tags`.`inactive` = 0 \
+                 AND `stories`.`user_id` = ? \
+                 GROUP BY `tags`.`id` \
+                 ORDER BY `count` desc LIMIT 1",
+            ),
+            (
+                "user.top_tag_details",
+                "SELECT  `tags`.* \
+                 FROM `tags` \
+                 WHERE `tags`.`id` = ?",
+            ),
+            (
+                "story.comments",
+                "SELECT `comments`.*, \
+                 `comments`.`upvotes` - `comments`.`downvotes` AS saldo \
+                 FROM `comments` \
+                 WHERE `comments`.`story_id` = ? \
+                 ORDER BY \
+                 saldo ASC, \
+                 confidence DESC",
+            ),
+        ]
+    }
+}
@@ -0,0 +1,87 @@
+use super::QueryProvider;
+
+/// Queries against the schema of the real site, with all the computed columns
+/// (`hotness`, `upvotes`, `confidence`, ...) maintained by the pages themselves.
+pub(crate) struct Original;
+
+impl QueryProvider for Original {
+    fn queries(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            (
+                "notifications.replies",
+                "SELECT COUNT(*) \
+                 FROM `replying_comments_for_count`
+                 WHERE `replying_comments_for_count`.`user_id` = ? \
+                 GROUP BY `replying_comments_for_count`.`user_id` \
+                 ",
+            ),
+            (
+                "replies.replies",
+                "SELECT `replying_comments_for_count`.* \
+                 FROM `replying_comments_for_count` \
+                 WHERE `replying_comments_for_count`.`user_id` = ? \
+                 ORDER BY `replying_comments_for_count`.`id` DESC \
+                 LIMIT 25",
+            ),
+            (
+                "frontpage.stories",
+                "SELECT  `stories`.* FROM `stories` \
+                 WHERE `stories`.`merged_story_id` IS NULL \
+                 AND `stories`.`is_expired` = 0 \
+                 AND ((CAST(upvotes AS signed) - CAST(downvotes AS signed)) >= 0) \
+                 ORDER BY hotness LIMIT ? OFFSET ?",
+            ),
+            (
+                "frontpage.filtered_taggings",
+                "SELECT `taggings`.`story_id` \
+                 FROM `taggings` \
+                 WHERE `taggings`.`story_id` IN ({stories}) \
+                 AND `taggings`.`tag_id` IN ({tags})",
+            ),
+            (
+                "recent.stories",
+                "SELECT  `stories`.* FROM `stories` \
+                 WHERE `stories`.`merged_story_id` IS NULL \
+                 AND `stories`.`is_expired` = 0 \
+                 AND CAST(upvotes AS signed) - CAST(downvotes AS signed) <= 5 \
+                 ORDER BY stories.id DESC LIMIT ? OFFSET ?",
+            ),
+            (
+                "recent.filtered_taggings",
+                "SELECT `taggings`.`story_id` \
+                 FROM `taggings` \
+                 WHERE `taggings`.`story_id` IN ({stories}) \
+                 AND `taggings`.`tag_id` IN ({tags})",
+            ),
+            (
+                "tag.stories",
+                "SELECT  `stories`.* FROM `stories` \
+                 INNER JOIN `taggings` ON `taggings`.`story_id` = `stories`.`id` \
+                 WHERE `stories`.`merged_story_id` IS NULL \
+                 AND `stories`.`is_expired` = 0 \
+                 AND ((CAST(upvotes AS signed) - CAST(downvotes AS signed)) >= 0) \
+                 AND `taggings`.`tag_id` = ? \
+                 ORDER BY hotness LIMIT ? OFFSET ?",
+            ),
+            (
+                "user.top_tag",
+                "SELECT  `tags`.* FROM `tags` \
+                 INNER JOIN `taggings` ON `taggings`.`tag_id` = `tags`.`id` \
+                 INNER JOIN `stories` ON `stories`.`id` = `taggings`.`story_id` \
+                 WHERE `tags`.`inactive` = 0 \
+                 AND `stories`.`user_id` = ? \
+                 GROUP BY `tags`.`id` \
+                 ORDER BY COUNT(*) desc LIMIT 1",
+            ),
+            (
+                "story.comments",
+                "SELECT `comments`.* \
+                 FROM `comments` \
+                 WHERE `comments`.`story_id` = ? \
+                 ORDER BY \
+                 (CAST(upvotes AS signed) - CAST(downvotes AS signed)) < 0 ASC, \
+                 confidence DESC",
+            ),
+        ]
+    }
+}
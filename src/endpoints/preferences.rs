@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use rand::Rng;
+use std::future::{self, Future};
+use trawler::UserId;
+
+use super::tag_filters::TagFilters;
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// Seeding a user's tag filters, hidden stories and saved stories while priming, so that the
+/// pages that read them find some.
+pub(crate) struct Preferences {
+    pub(crate) acting_as: Option<UserId>,
+    /// The names of the tags the user filters.
+    pub(crate) filters: Vec<String>,
+    /// How many (random) stories the user has hidden.
+    pub(crate) hidden: u32,
+    /// How many (random) stories the user has saved.
+    pub(crate) saved: u32,
+}
+
+#[async_trait]
+impl Page for Preferences {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Preferences {
+            acting_as,
+            filters,
+            hidden,
+            saved,
+        } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        if !filters.is_empty() {
+            let filters = TagFilters {
+                acting_as,
+                tags: filters,
+            };
+            (c, _) = filters.handle(future::ready(Ok(c)), q).await?;
+        }
+
+        if hidden == 0 && saved == 0 {
+            return Ok((c, false));
+        }
+
+        // story ids are handed out sequentially, so any id up to the latest one will do
+        let latest = c
+            .query_first::<Row, _>(q.get(
+                "preferences.latest_story",
+                "SELECT MAX(`stories`.`id`) AS `id` FROM `stories`",
+            ))
+            .await?
+            .and_then(|story| story.get::<Option<u32>, _>("id").unwrap());
+        let Some(latest) = latest else {
+            return Ok((c, false));
+        };
+        let (hidden, saved): (Vec<u32>, Vec<u32>) = {
+            let mut rng = rand::thread_rng();
+            (
+                (0..hidden).map(|_| rng.gen_range(1..=latest)).collect(),
+                (0..saved).map(|_| rng.gen_range(1..=latest)).collect(),
+            )
+        };
+
+        for story in hidden {
+            c.exec_drop(
+                q.get(
+                    "preferences.insert_hidden",
+                    "INSERT IGNORE INTO `hidden_stories` (`user_id`, `story_id`) \
+                     VALUES (?, ?)",
+                ),
+                (user, story),
+            )
+            .await?;
+        }
+
+        let now = chrono::Local::now().naive_local();
+        for story in saved {
+            c.exec_drop(
+                q.get(
+                    "preferences.insert_saved",
+                    "INSERT IGNORE INTO `saved_stories` \
+                     (`created_at`, `updated_at`, `user_id`, `story_id`) \
+                     VALUES (?, ?, ?, ?)",
+                ),
+                (now, now, user, story),
+            )
+            .await?;
+        }
+
+        Ok((c, false))
+    }
+}
@@ -0,0 +1,186 @@
+use chrono::NaiveDateTime;
+use mysql_async::Row;
+
+/// The window (in seconds) over which a story's age outweighs one order of magnitude of votes.
+const HOTNESS_WINDOW: i64 = 60 * 60 * 22;
+
+/// Everything that goes into a story's hotness, mirroring `Story::calculated_hotness` in the
+/// lobsters source. Lower is hotter, which is what the frontpage orders by.
+pub(crate) struct Hotness {
+    pub(crate) upvotes: i64,
+    pub(crate) downvotes: i64,
+    pub(crate) created_at: NaiveDateTime,
+    /// Sum of the `hotness_mod` of the story's tags.
+    pub(crate) tags_mod: f64,
+    /// Whether the submitter links to their own work, which gives the story a slight bump.
+    pub(crate) authored_link: bool,
+    /// `(upvotes, downvotes)` of the comments on the story not made by its submitter.
+    pub(crate) comment_votes: Vec<(i64, i64)>,
+    /// `(upvotes, downvotes)` of the stories merged into this one.
+    pub(crate) merged_votes: Vec<(i64, i64)>,
+}
+
+impl Hotness {
+    /// Start from the `stories` row of the story, before its tags, comments and merged stories
+    /// are taken into account.
+    pub(crate) fn of(story: &Row) -> Self {
+        let url = story.get::<Option<String>, _>("url").unwrap();
+        let user_is_author = story.get::<Option<bool>, _>("user_is_author").unwrap();
+        let (upvotes, downvotes) = votes(story);
+        Hotness {
+            upvotes,
+            downvotes,
+            created_at: story
+                .get::<Option<NaiveDateTime>, _>("created_at")
+                .unwrap()
+                .unwrap_or_else(|| chrono::Local::now().naive_local()),
+            tags_mod: 0.0,
+            authored_link: user_is_author.unwrap_or(false) && url.is_some_and(|u| !u.is_empty()),
+            comment_votes: Vec::new(),
+            merged_votes: Vec::new(),
+        }
+    }
+
+    pub(crate) fn compute(&self) -> f64 {
+        let base = self.tags_mod + if self.authored_link { 0.25 } else { 0.0 };
+
+        // comment votes count for something too, but in stories that start out with a bad
+        // hotness mod, only the downvotes do
+        let mut cpoints = self
+            .comment_votes
+            .iter()
+            .map(|&(up, down)| {
+                if base < 0.0 {
+                    down as f64 * -0.5
+                } else {
+                    (up + 1 - down) as f64
+                }
+            })
+            .sum::<f64>()
+            * 0.5;
+        cpoints += self
+            .merged_votes
+            .iter()
+            .map(|&(up, down)| (up - down) as f64)
+            .sum::<f64>();
+
+        // lots of comments but few votes is probably a bad story
+        let upvotes = self.upvotes + self.merged_votes.iter().map(|&(up, _)| up).sum::<i64>();
+        if cpoints > upvotes as f64 {
+            cpoints = upvotes as f64;
+        }
+
+        let score = self.upvotes - self.downvotes;
+        let order = ((score + 1).abs() as f64 + cpoints).max(1.0).log10();
+        let sign = score.signum() as f64;
+        let age = self.created_at.and_utc().timestamp() as f64 / HOTNESS_WINDOW as f64;
+
+        let hotness = -(order * sign + base + age);
+        (hotness * 1e7).round() / 1e7
+    }
+}
+
+/// The lower bound of the Wilson score interval of a comment's votes, mirroring
+/// `Comment::calculated_confidence` in the lobsters source (down to its `1 / (2n * z^2)` term).
+/// Comments on a story are listed by descending confidence.
+pub(crate) fn confidence(upvotes: i64, downvotes: i64) -> f64 {
+    let n = (upvotes + downvotes) as f64;
+    if n == 0.0 {
+        return 0.0;
+    }
+
+    // 80% confidence
+    let z = 1.281551565545;
+    let p = upvotes as f64 / n;
+    let left = p + 1.0 / (2.0 * n * z * z);
+    let right = z * (p * ((1.0 - p) / n) + z * z / (4.0 * n * n)).sqrt();
+    let under = 1.0 + z * z / n;
+    (left - right) / under
+}
+
+/// The `(upvotes, downvotes)` of a `comments` or `stories` row.
+pub(crate) fn votes(row: &Row) -> (i64, i64) {
+    (
+        row.get::<i64, _>("upvotes").unwrap(),
+        row.get::<i64, _>("downvotes").unwrap(),
+    )
+}
+
+/// The `hotness_mod` of a `tags` row.
+pub(crate) fn hotness_mod(tag: &Row) -> f64 {
+    tag.get::<Option<f64>, _>("hotness_mod")
+        .unwrap()
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn story(upvotes: i64, downvotes: i64, created_at: NaiveDateTime) -> Hotness {
+        Hotness {
+            upvotes,
+            downvotes,
+            created_at,
+            tags_mod: 0.0,
+            authored_link: false,
+            comment_votes: Vec::new(),
+            merged_votes: Vec::new(),
+        }
+    }
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2020, 1, 1)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn newer_and_upvoted_stories_are_hotter() {
+        // lower is hotter
+        assert!(story(1, 0, at(12)).compute() < story(1, 0, at(0)).compute());
+        assert!(story(10, 0, at(0)).compute() < story(1, 0, at(0)).compute());
+        assert!(story(1, 0, at(0)).compute() < story(0, 5, at(0)).compute());
+
+        // stories less than a window apart still differ in hotness
+        assert_ne!(story(1, 0, at(1)).compute(), story(1, 0, at(0)).compute());
+    }
+
+    #[test]
+    fn tags_and_authored_links_adjust_hotness() {
+        let plain = story(1, 0, at(0));
+        let mut boosted = story(1, 0, at(0));
+        boosted.tags_mod = 0.5;
+        assert!(boosted.compute() < plain.compute());
+
+        let mut authored = story(1, 0, at(0));
+        authored.authored_link = true;
+        assert!(authored.compute() < plain.compute());
+    }
+
+    #[test]
+    fn comment_points_are_capped_by_upvotes() {
+        let plain = story(1, 0, at(0));
+        let mut discussed = story(1, 0, at(0));
+        discussed.comment_votes = vec![(10, 0); 10];
+        let mut more_discussed = story(1, 0, at(0));
+        more_discussed.comment_votes = vec![(10, 0); 100];
+
+        assert!(discussed.compute() < plain.compute());
+        assert_eq!(discussed.compute(), more_discussed.compute());
+    }
+
+    #[test]
+    fn confidence_bounds() {
+        assert_eq!(confidence(0, 0), 0.0);
+        assert!(confidence(100, 0) < 1.0);
+        assert!(confidence(0, 1) < confidence(1, 0));
+
+        // more upvotes, fewer downvotes, and more votes at the same ratio all mean more confidence
+        assert!(confidence(2, 0) > confidence(1, 0));
+        assert!(confidence(5, 1) > confidence(5, 2));
+        assert!(confidence(100, 10) > confidence(10, 1));
+    }
+}
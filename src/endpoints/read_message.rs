@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::UserId;
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user opening the oldest private message they have not read yet.
+pub(crate) struct ReadMessage {
+    pub(crate) acting_as: Option<UserId>,
+}
+
+#[async_trait]
+impl Page for ReadMessage {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let ReadMessage { acting_as } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let message = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "read_message.message",
+                    "SELECT `messages`.* \
+                     FROM `messages` \
+                     WHERE `messages`.`recipient_user_id` = ? \
+                     AND `messages`.`has_been_read` = 0 \
+                     AND `messages`.`deleted_by_recipient` = 0 \
+                     ORDER BY `messages`.`id` ASC \
+                     LIMIT 1",
+                ),
+                (user,),
+            )
+            .await?;
+        let message = match message {
+            Some(message) => message,
+            None => {
+                return Ok((c, true));
+            }
+        };
+        let author = message.get::<u32, _>("author_user_id").unwrap();
+        let message = message.get::<u32, _>("id").unwrap();
+
+        c.exec_drop(
+            q.get(
+                "read_message.author",
+                "SELECT `users`.* FROM `users` WHERE `users`.`id` = ?",
+            ),
+            (author,),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "read_message.mark_read",
+                "UPDATE `messages` \
+                 SET `messages`.`has_been_read` = 1 \
+                 WHERE `messages`.`id` = ?",
+            ),
+            (message,),
+        )
+        .await?;
+
+        let c = super::update_unread_messages(c, q, user).await?;
+
+        Ok((c, true))
+    }
+}
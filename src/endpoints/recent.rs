@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::HashSet;
+use std::future::Future;
+use trawler::UserId;
+
+use super::{listing, Page, STORIES_PER_PAGE};
+use crate::queries::QueryCatalog;
+
+pub(crate) struct Recent {
+    pub(crate) acting_as: Option<UserId>,
+    /// Which page of the listing, starting at 0 for the first.
+    pub(crate) page: u32,
+}
+
+#[async_trait]
+impl Page for Recent {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Recent { acting_as, page } = self;
+        // /recent is a little weird:
+        // https://github.com/lobsters/lobsters/blob/50b4687aeeec2b2d60598f63e06565af226f93e3/app/models/story_repository.rb#L41
+        // but it *basically* just looks for stories in the past few days
+        // because all our stories are for the same day, we add a LIMIT
+        // also note the `NOW()` hack to support dbs primed a while ago
+        let mut c = c.await?;
+        let (users, stories) = c
+            .exec_iter(
+                q.variant("recent.stories"),
+                (STORIES_PER_PAGE + 1, page * STORIES_PER_PAGE),
+            )
+            .await?
+            .reduce_and_drop(
+                (HashSet::new(), HashSet::new()),
+                |(mut users, mut stories), story: Row| {
+                    users.insert(story.get::<u32, _>("user_id").unwrap());
+                    stories.insert(story.get::<u32, _>("id").unwrap());
+                    (users, stories)
+                },
+            )
+            .await?;
+
+        // pages past the end of the listing are empty, but the first page never is
+        if stories.is_empty() {
+            assert!(page > 0, "got no stories from /recent");
+            return Ok((c, true));
+        }
+
+        if let Some(uid) = acting_as {
+            c = listing::filters(c, q, "recent", uid, &stories).await?;
+        }
+        c = listing::stories(c, q, "recent", acting_as, &users, &stories).await?;
+
+        Ok((c, true))
+    }
+}
@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::HashSet;
+use std::future::Future;
+use trawler::UserId;
+
+use super::listing::ids_in;
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// The `/replies/unread` page: the unread replies to a user's comments and stories, which are
+/// marked as read by viewing them.
+pub(crate) struct Replies {
+    pub(crate) acting_as: Option<UserId>,
+}
+
+#[async_trait]
+impl Page for Replies {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Replies { acting_as } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let (comments, stories) = c
+            .exec_iter(q.variant("replies.replies"), (user,))
+            .await?
+            .reduce_and_drop(
+                (Vec::new(), HashSet::new()),
+                |(mut comments, mut stories), reply: Row| {
+                    comments.push(reply.get::<u32, _>("id").unwrap());
+                    stories.insert(reply.get::<u32, _>("story_id").unwrap());
+                    (comments, stories)
+                },
+            )
+            .await?;
+
+        if comments.is_empty() {
+            return Ok((c, true));
+        }
+
+        let comments = ids_in(&comments);
+        let users = c
+            .query_iter(
+                q.get(
+                    "replies.comments",
+                    "SELECT `comments`.* FROM `comments` \
+                     WHERE `comments`.`id` IN ({comments})",
+                )
+                .replace("{comments}", &comments),
+            )
+            .await?
+            .reduce_and_drop(HashSet::new(), |mut users, comment: Row| {
+                users.insert(comment.get::<u32, _>("user_id").unwrap());
+                users
+            })
+            .await?;
+
+        let users = ids_in(&users);
+        c.query_drop(
+            q.get(
+                "replies.users",
+                "SELECT `users`.* FROM `users` WHERE `users`.`id` IN ({users})",
+            )
+            .replace("{users}", &users),
+        )
+        .await?;
+
+        let stories = ids_in(&stories);
+        c.query_drop(
+            q.get(
+                "replies.stories",
+                "SELECT `stories`.* FROM `stories` \
+                 WHERE `stories`.`id` IN ({stories})",
+            )
+            .replace("{stories}", &stories),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "replies.votes",
+                "SELECT `votes`.* FROM `votes` \
+                 WHERE `votes`.`user_id` = ? \
+                 AND `votes`.`comment_id` IN ({comments})",
+            )
+            .replace("{comments}", &comments),
+            (user,),
+        )
+        .await?;
+
+        // having seen them, the replies are no longer unread
+        c.exec_drop(
+            q.get(
+                "replies.mark_read",
+                "UPDATE `read_ribbons` \
+                 SET `read_ribbons`.`updated_at` = ? \
+                 WHERE `read_ribbons`.`user_id` = ? \
+                 AND `read_ribbons`.`story_id` IN ({stories})",
+            )
+            .replace("{stories}", &stories),
+            (chrono::Local::now().naive_local(), user),
+        )
+        .await?;
+
+        Ok((c, true))
+    }
+}
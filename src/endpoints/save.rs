@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::{StoryId, UserId};
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user saving a story for later, or unsaving it if they had already saved it.
+pub(crate) struct Save {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) story: StoryId,
+}
+
+#[async_trait]
+impl Page for Save {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Save { acting_as, story } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let story = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "save.story",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&story[..]).unwrap(),),
+            )
+            .await?
+            .unwrap();
+        let story = story.get::<u32, _>("id").unwrap();
+
+        let saved = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "save.saved",
+                    "SELECT `saved_stories`.* \
+                     FROM `saved_stories` \
+                     WHERE `saved_stories`.`user_id` = ? \
+                     AND `saved_stories`.`story_id` = ?",
+                ),
+                (user, story),
+            )
+            .await?;
+
+        match saved {
+            Some(saved) => {
+                c.exec_drop(
+                    q.get(
+                        "save.unsave",
+                        "DELETE FROM `saved_stories` \
+                         WHERE `saved_stories`.`id` = ?",
+                    ),
+                    (saved.get::<u64, _>("id").unwrap(),),
+                )
+                .await?;
+            }
+            None => {
+                let now = chrono::Local::now().naive_local();
+                c.exec_drop(
+                    q.get(
+                        "save.save",
+                        "INSERT INTO `saved_stories` \
+                         (`created_at`, `updated_at`, `user_id`, `story_id`) \
+                         VALUES (?, ?, ?, ?)",
+                    ),
+                    (now, now, user, story),
+                )
+                .await?;
+            }
+        }
+
+        Ok((c, false))
+    }
+}
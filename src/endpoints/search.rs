@@ -0,0 +1,191 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::HashSet;
+use std::future::Future;
+use trawler::UserId;
+
+use super::listing::ids_in;
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// What to search through.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum SearchWhat {
+    Stories,
+    Comments,
+}
+
+/// Searching stories or comments for some terms, using the fulltext indexes like the real site
+/// does on MySQL.
+pub(crate) struct Search {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) what: SearchWhat,
+    pub(crate) terms: String,
+}
+
+#[async_trait]
+impl Page for Search {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Search {
+            acting_as,
+            what,
+            terms,
+        } = self;
+        let mut c = c.await?;
+
+        let (results, users, stories) = match what {
+            SearchWhat::Stories => {
+                c.exec_iter(
+                    q.get(
+                        "search.stories",
+                        "SELECT `stories`.*, \
+                         (MATCH(`stories`.`title`) AGAINST(? IN BOOLEAN MODE) * 2) + \
+                         MATCH(`stories`.`description`) AGAINST(? IN BOOLEAN MODE) + \
+                         MATCH(`stories`.`story_cache`) AGAINST(? IN BOOLEAN MODE) \
+                         AS `relevance` \
+                         FROM `stories` \
+                         WHERE (MATCH(`stories`.`title`) AGAINST(? IN BOOLEAN MODE) \
+                         OR MATCH(`stories`.`description`) AGAINST(? IN BOOLEAN MODE) \
+                         OR MATCH(`stories`.`story_cache`) AGAINST(? IN BOOLEAN MODE)) \
+                         AND `stories`.`is_expired` = 0 \
+                         AND `stories`.`merged_story_id` IS NULL \
+                         ORDER BY `relevance` DESC \
+                         LIMIT 20 OFFSET 0",
+                    ),
+                    (&terms, &terms, &terms, &terms, &terms, &terms),
+                )
+                .await?
+                .reduce_and_drop(
+                    (Vec::new(), HashSet::new(), HashSet::new()),
+                    |(mut results, mut users, mut stories), story: Row| {
+                        let id = story.get::<u32, _>("id").unwrap();
+                        results.push(id);
+                        users.insert(story.get::<u32, _>("user_id").unwrap());
+                        stories.insert(id);
+                        (results, users, stories)
+                    },
+                )
+                .await?
+            }
+            SearchWhat::Comments => {
+                c.exec_iter(
+                    q.get(
+                        "search.comments",
+                        "SELECT `comments`.*, \
+                         MATCH(`comments`.`comment`) AGAINST(? IN BOOLEAN MODE) AS `relevance` \
+                         FROM `comments` \
+                         WHERE MATCH(`comments`.`comment`) AGAINST(? IN BOOLEAN MODE) \
+                         AND `comments`.`is_deleted` = 0 \
+                         AND `comments`.`is_moderated` = 0 \
+                         ORDER BY `relevance` DESC \
+                         LIMIT 20 OFFSET 0",
+                    ),
+                    (&terms, &terms),
+                )
+                .await?
+                .reduce_and_drop(
+                    (Vec::new(), HashSet::new(), HashSet::new()),
+                    |(mut results, mut users, mut stories), comment: Row| {
+                        results.push(comment.get::<u32, _>("id").unwrap());
+                        users.insert(comment.get::<u32, _>("user_id").unwrap());
+                        stories.insert(comment.get::<u32, _>("story_id").unwrap());
+                        (results, users, stories)
+                    },
+                )
+                .await?
+            }
+        };
+
+        if results.is_empty() {
+            return Ok((c, true));
+        }
+
+        let users = ids_in(&users);
+        c.query_drop(
+            q.get(
+                "search.users",
+                "SELECT `users`.* FROM `users` WHERE `users`.`id` IN ({users})",
+            )
+            .replace("{users}", &users),
+        )
+        .await?;
+
+        let stories = ids_in(&stories);
+        let results = ids_in(&results);
+        match what {
+            SearchWhat::Stories => {
+                let tags = c
+                    .query_iter(
+                        q.get(
+                            "search.taggings",
+                            "SELECT `taggings`.* FROM `taggings` \
+                             WHERE `taggings`.`story_id` IN ({stories})",
+                        )
+                        .replace("{stories}", &stories),
+                    )
+                    .await?
+                    .reduce_and_drop(HashSet::new(), |mut tags, tagging: Row| {
+                        tags.insert(tagging.get::<u32, _>("tag_id").unwrap());
+                        tags
+                    })
+                    .await?;
+
+                let tags = ids_in(&tags);
+                c.query_drop(
+                    q.get(
+                        "search.tags",
+                        "SELECT `tags`.* FROM `tags` WHERE `tags`.`id` IN ({tags})",
+                    )
+                    .replace("{tags}", &tags),
+                )
+                .await?;
+
+                if let Some(uid) = acting_as {
+                    c.exec_drop(
+                        q.get(
+                            "search.story_votes",
+                            "SELECT `votes`.* FROM `votes` \
+                             WHERE `votes`.`user_id` = ? \
+                             AND `votes`.`story_id` IN ({stories}) \
+                             AND `votes`.`comment_id` IS NULL",
+                        )
+                        .replace("{stories}", &stories),
+                        (uid,),
+                    )
+                    .await?;
+                }
+            }
+            SearchWhat::Comments => {
+                c.query_drop(
+                    q.get(
+                        "search.comment_stories",
+                        "SELECT `stories`.* FROM `stories` \
+                         WHERE `stories`.`id` IN ({stories})",
+                    )
+                    .replace("{stories}", &stories),
+                )
+                .await?;
+
+                if let Some(uid) = acting_as {
+                    c.exec_drop(
+                        q.get(
+                            "search.comment_votes",
+                            "SELECT `votes`.* FROM `votes` \
+                             WHERE `votes`.`user_id` = ? \
+                             AND `votes`.`comment_id` IN ({comments})",
+                        )
+                        .replace("{comments}", &results),
+                        (uid,),
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        Ok((c, true))
+    }
+}
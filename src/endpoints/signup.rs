@@ -0,0 +1,112 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::UserId;
+
+use super::{Credentials, Page};
+use crate::queries::QueryCatalog;
+
+/// Someone following the link in the oldest outstanding invitation and signing up with it,
+/// which uses up the invitation.
+pub(crate) struct Signup {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) username: String,
+    pub(crate) credentials: Credentials,
+}
+
+#[async_trait]
+impl Page for Signup {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Signup {
+            acting_as: _,
+            username,
+            credentials,
+        } = self;
+        let mut c = c.await?;
+
+        // whose email gets read first
+        let code = c
+            .query_first::<String, _>(q.get(
+                "signup.oldest_invitation",
+                "SELECT `invitations`.`code` \
+                 FROM `invitations` \
+                 ORDER BY `invitations`.`id` ASC \
+                 LIMIT 1",
+            ))
+            .await?;
+        let code = match code {
+            Some(code) => code,
+            None => return Ok((c, false)),
+        };
+
+        // the signup form, and then its submission, both look the invitation up by its code
+        let invitation = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "signup.invitation",
+                    "SELECT `invitations`.* \
+                     FROM `invitations` \
+                     WHERE `invitations`.`code` = ?",
+                ),
+                (&code,),
+            )
+            .await?;
+        let invitation = match invitation {
+            Some(invitation) => invitation,
+            None => return Ok((c, false)),
+        };
+        let id = invitation.get::<u32, _>("id").unwrap();
+        let inviter = invitation.get::<Option<u32>, _>("user_id").unwrap();
+        let email = invitation.get::<String, _>("email").unwrap();
+
+        let taken = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "signup.username_taken",
+                    "SELECT  1 AS one FROM `users` \
+                     WHERE `users`.`username` = ?",
+                ),
+                (&username,),
+            )
+            .await?;
+        if taken.is_some() {
+            return Ok((c, false));
+        }
+
+        c.exec_drop(
+            q.get(
+                "signup.insert_user",
+                "INSERT INTO `users` \
+                 (`username`, `email`, `password_digest`, `created_at`, \
+                 `session_token`, `rss_token`, `mailing_list_token`, `invited_by_user_id`) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            ),
+            (
+                username,
+                email,
+                credentials.password_digest,
+                chrono::Local::now().naive_local(),
+                credentials.session_token,
+                credentials.rss_token,
+                credentials.mailing_list_token,
+                inviter,
+            ),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "signup.delete_invitation",
+                "DELETE FROM `invitations` WHERE `invitations`.`id` = ?",
+            ),
+            (id,),
+        )
+        .await?;
+
+        Ok((c, false))
+    }
+}
@@ -0,0 +1,247 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::HashSet;
+use std::future::Future;
+use trawler::{StoryId, UserId};
+
+use super::listing::ids_in;
+use super::Page;
+use crate::queries::QueryCatalog;
+
+pub(crate) struct Story {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) id: StoryId,
+}
+
+#[async_trait]
+impl Page for Story {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Story { acting_as, id } = self;
+        // XXX: at the end there are also a bunch of repeated, seemingly superfluous queries
+        let mut c = c.await?;
+        let mut story = c
+            .exec_iter(
+                q.get(
+                    "story.story",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&id[..]).unwrap(),),
+            )
+            .await?
+            .collect_and_drop::<Row>()
+            .await?;
+        let mut story = story.swap_remove(0);
+
+        // the real site redirects to the story that this one was merged into
+        if let Some(merged_into) = story.get::<Option<u32>, _>("merged_story_id").unwrap() {
+            story = c
+                .exec_first::<Row, _, _>(
+                    q.get(
+                        "story.merged_into",
+                        "SELECT `stories`.* \
+                         FROM `stories` \
+                         WHERE `stories`.`id` = ?",
+                    ),
+                    (merged_into,),
+                )
+                .await?
+                .unwrap();
+        }
+        let author = story.get::<u32, _>("user_id").unwrap();
+        let story = story.get::<u32, _>("id").unwrap();
+        c.exec_drop(
+            q.get(
+                "story.author",
+                "SELECT `users`.* FROM `users` WHERE `users`.`id` = ?",
+            ),
+            (author,),
+        )
+        .await?;
+
+        // NOTE: technically this happens before the select from user...
+        if let Some(uid) = acting_as {
+            // keep track of when the user last saw this story
+            // NOTE: *technically* the update only happens at the end...
+            let rr = c
+                .exec_first::<Row, _, _>(
+                    q.get(
+                        "story.read_ribbon",
+                        "SELECT  `read_ribbons`.* \
+                         FROM `read_ribbons` \
+                         WHERE `read_ribbons`.`user_id` = ? \
+                         AND `read_ribbons`.`story_id` = ?",
+                    ),
+                    (&uid, &story),
+                )
+                .await?;
+            let now = chrono::Local::now().naive_local();
+            match rr {
+                None => {
+                    c.exec_drop(
+                        q.get(
+                            "story.insert_read_ribbon",
+                            "INSERT INTO `read_ribbons` \
+                             (`created_at`, `updated_at`, `user_id`, `story_id`) \
+                             VALUES (?, ?, ?, ?)",
+                        ),
+                        (now, now, uid, story),
+                    )
+                    .await?
+                }
+                Some(rr) => {
+                    c.exec_drop(
+                        q.get(
+                            "story.update_read_ribbon",
+                            "UPDATE `read_ribbons` \
+                             SET `read_ribbons`.`updated_at` = ? \
+                             WHERE `read_ribbons`.`id` = ?",
+                        ),
+                        (now, rr.get::<u32, _>("id").unwrap()),
+                    )
+                    .await?
+                }
+            };
+        }
+
+        // stories merged into this one are listed along with it, by their own authors
+        let merged_authors = c
+            .exec_iter(
+                q.get(
+                    "story.merged_stories",
+                    "SELECT `stories`.`id`, `stories`.`user_id` \
+                     FROM `stories` \
+                     WHERE `stories`.`merged_story_id` = ?",
+                ),
+                (story,),
+            )
+            .await?
+            .reduce_and_drop(HashSet::new(), |mut authors, story: Row| {
+                authors.insert(story.get::<u32, _>("user_id").unwrap());
+                authors
+            })
+            .await?;
+
+        let (users, comments, hats) = c
+            .exec_iter(q.variant("story.comments"), (story,))
+            .await?
+            .reduce_and_drop(
+                (merged_authors, HashSet::new(), HashSet::new()),
+                |(mut users, mut comments, mut hats), comment: Row| {
+                    users.insert(comment.get::<u32, _>("user_id").unwrap());
+                    comments.insert(comment.get::<u32, _>("id").unwrap());
+                    hats.extend(comment.get::<Option<u32>, _>("hat_id").unwrap());
+                    (users, comments, hats)
+                },
+            )
+            .await?;
+
+        // get user info for all commenters and merged story authors
+        let users = ids_in(&users);
+        c.query_drop(
+            q.get(
+                "story.commenters",
+                "SELECT `users`.* FROM `users` WHERE `users`.`id` IN ({users})",
+            )
+            .replace("{users}", &users),
+        )
+        .await?;
+
+        // and the hats worn by commenters
+        if !hats.is_empty() {
+            let hats = ids_in(&hats);
+            c.query_drop(
+                q.get(
+                    "story.hats",
+                    "SELECT `hats`.* FROM `hats` WHERE `hats`.`id` IN ({hats})",
+                )
+                .replace("{hats}", &hats),
+            )
+            .await?;
+        }
+
+        // get comment votes
+        // XXX: why?!
+        let comments = ids_in(&comments);
+        c.query_drop(
+            q.get(
+                "story.comment_votes",
+                "SELECT `votes`.* FROM `votes` WHERE `votes`.`comment_id` IN ({comments})",
+            )
+            .replace("{comments}", &comments),
+        )
+        .await?;
+
+        // NOTE: lobste.rs here fetches the user list again. unclear why?
+        if let Some(uid) = acting_as {
+            c.exec_drop(
+                q.get(
+                    "story.vote",
+                    "SELECT `votes`.* \
+                     FROM `votes` \
+                     WHERE `votes`.`user_id` = ? \
+                     AND `votes`.`story_id` = ? \
+                     AND `votes`.`comment_id` IS NULL",
+                ),
+                (uid, story),
+            )
+            .await?;
+            c.exec_drop(
+                q.get(
+                    "story.hidden",
+                    "SELECT `hidden_stories`.* \
+                     FROM `hidden_stories` \
+                     WHERE `hidden_stories`.`user_id` = ? \
+                     AND `hidden_stories`.`story_id` = ?",
+                ),
+                (uid, story),
+            )
+            .await?;
+            c.exec_drop(
+                q.get(
+                    "story.saved",
+                    "SELECT `saved_stories`.* \
+                     FROM `saved_stories` \
+                     WHERE `saved_stories`.`user_id` = ? \
+                     AND `saved_stories`.`story_id` = ?",
+                ),
+                (uid, story),
+            )
+            .await?;
+        }
+
+        let tags = c
+            .exec_iter(
+                q.get(
+                    "story.taggings",
+                    "SELECT `taggings`.* \
+                     FROM `taggings` \
+                     WHERE `taggings`.`story_id` = ?",
+                ),
+                (story,),
+            )
+            .await?
+            .reduce_and_drop(HashSet::new(), |mut tags, tagging: Row| {
+                tags.insert(tagging.get::<u32, _>("tag_id").unwrap());
+                tags
+            })
+            .await?;
+
+        let tags = ids_in(&tags);
+        c.query_drop(
+            q.get(
+                "story.tags",
+                "SELECT `tags`.* FROM `tags` WHERE `tags`.`id` IN ({tags})",
+            )
+            .replace("{tags}", &tags),
+        )
+        .await?;
+
+        Ok((c, true))
+    }
+}
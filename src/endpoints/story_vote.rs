@@ -0,0 +1,195 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::{StoryId, UserId, Vote};
+
+use super::ranking::{self, Hotness};
+use super::votes::{self, VoteChange};
+use super::Page;
+use crate::queries::QueryCatalog;
+
+pub(crate) struct StoryVote {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) story: StoryId,
+    pub(crate) vote: Vote,
+    /// Withdraw the user's existing vote instead of voting `vote`.
+    pub(crate) unvote: bool,
+}
+
+#[async_trait]
+impl Page for StoryVote {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let StoryVote {
+            acting_as,
+            story,
+            vote: v,
+            unvote,
+        } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+        let mut story = c
+            .exec_iter(
+                q.get(
+                    "story_vote.story",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&story[..]).unwrap(),),
+            )
+            .await?
+            .collect_and_drop::<Row>()
+            .await?;
+        let story = story.swap_remove(0);
+
+        let author = story.get::<u32, _>("user_id").unwrap();
+        let mut hotness = Hotness::of(&story);
+        let story = story.get::<u32, _>("id").unwrap();
+        let existing = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "story_vote.user_vote",
+                    "SELECT  `votes`.* \
+                     FROM `votes` \
+                     WHERE `votes`.`user_id` = ? \
+                     AND `votes`.`story_id` = ? \
+                     AND `votes`.`comment_id` IS NULL",
+                ),
+                (user, story),
+            )
+            .await?;
+
+        let change = VoteChange::new(existing.as_ref(), v, unvote);
+        change.record("story");
+
+        // TODO: technically need to re-load story under transaction
+
+        // NOTE: MySQL technically does everything inside this and_then in a transaction,
+        // but let's be nice to it
+        match change {
+            VoteChange::Noop => return Ok((c, false)),
+            VoteChange::New { up } => {
+                c.exec_drop(
+                    q.get(
+                        "story_vote.insert_vote",
+                        "INSERT INTO `votes` \
+                         (`user_id`, `story_id`, `vote`) \
+                         VALUES \
+                         (?, ?, ?)",
+                    ),
+                    (user, story, up as u8),
+                )
+                .await?;
+            }
+            VoteChange::Flip { up } => {
+                c.exec_drop(
+                    q.get(
+                        "story_vote.update_vote",
+                        "UPDATE `votes` \
+                         SET `votes`.`vote` = ? \
+                         WHERE `votes`.`id` = ?",
+                    ),
+                    (up as u8, existing.unwrap().get::<u64, _>("id").unwrap()),
+                )
+                .await?;
+            }
+            VoteChange::Unvote { .. } => {
+                c.exec_drop(
+                    q.get(
+                        "story_vote.delete_vote",
+                        "DELETE FROM `votes` \
+                         WHERE `votes`.`id` = ?",
+                    ),
+                    (existing.unwrap().get::<u64, _>("id").unwrap(),),
+                )
+                .await?;
+            }
+        }
+
+        c.exec_drop(
+            q.get(
+                "story_vote.update_karma",
+                "UPDATE `users` \
+                 SET `users`.`karma` = `users`.`karma` {karma} \
+                 WHERE `users`.`id` = ?",
+            )
+            .replace("{karma}", &votes::delta(change.karma())),
+            (author,),
+        )
+        .await?;
+
+        // get all the stuff needed to compute updated hotness
+        let (upvotes, downvotes) = change.deltas();
+        hotness.upvotes += upvotes;
+        hotness.downvotes += downvotes;
+        hotness.tags_mod = c
+            .exec_iter(
+                q.get(
+                    "story_vote.tags",
+                    "SELECT `tags`.* \
+                     FROM `tags` \
+                     INNER JOIN `taggings` ON `tags`.`id` = `taggings`.`tag_id` \
+                     WHERE `taggings`.`story_id` = ?",
+                ),
+                (story,),
+            )
+            .await?
+            .reduce_and_drop(0.0, |tags_mod, tag: Row| {
+                tags_mod + ranking::hotness_mod(&tag)
+            })
+            .await?;
+
+        hotness.comment_votes = c
+            .exec_iter(
+                q.get(
+                    "story_vote.comment_votes",
+                    "SELECT \
+                     `comments`.`upvotes`, \
+                     `comments`.`downvotes` \
+                     FROM `comments` \
+                     JOIN `stories` ON (`stories`.`id` = `comments`.`story_id`) \
+                     WHERE `comments`.`story_id` = ? \
+                     AND `comments`.`user_id` <> `stories`.`user_id`",
+                ),
+                (story,),
+            )
+            .await?
+            .map_and_drop(|comment: Row| ranking::votes(&comment))
+            .await?;
+
+        hotness.merged_votes = c
+            .exec_iter(
+                q.get(
+                    "story_vote.merged_stories",
+                    "SELECT `stories`.`id`, `stories`.`upvotes`, `stories`.`downvotes` \
+                     FROM `stories` \
+                     WHERE `stories`.`merged_story_id` = ?",
+                ),
+                (story,),
+            )
+            .await?
+            .map_and_drop(|merged: Row| ranking::votes(&merged))
+            .await?;
+
+        c.exec_drop(
+            q.get(
+                "story_vote.update_story",
+                "UPDATE stories SET \
+                 stories.upvotes = stories.upvotes {upvotes}, \
+                 stories.downvotes = stories.downvotes {downvotes}, \
+                 stories.hotness = ? \
+                 WHERE stories.id = ?",
+            )
+            .replace("{upvotes}", &votes::delta(upvotes))
+            .replace("{downvotes}", &votes::delta(downvotes)),
+            (hotness.compute(), story),
+        )
+        .await?;
+
+        Ok((c, false))
+    }
+}
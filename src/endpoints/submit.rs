@@ -0,0 +1,243 @@
+use async_trait::async_trait;
+use metrics::counter;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::{StoryId, UserId};
+
+use super::ranking::{self, Hotness};
+use super::Page;
+use crate::lorem;
+use crate::queries::QueryCatalog;
+use crate::urls;
+
+pub(crate) struct Submit {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) id: StoryId,
+    pub(crate) title: String,
+    /// The names of the tags to submit the story with.
+    pub(crate) tags: Vec<String>,
+    /// What the story links to, if it isn't a text post.
+    pub(crate) url: Option<String>,
+    pub(crate) priming: bool,
+}
+
+#[async_trait]
+impl Page for Submit {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Submit {
+            acting_as,
+            id,
+            title,
+            tags,
+            url,
+            priming,
+        } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        // check that tags are active
+        let params = tags.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let (tags, tags_mod) = c
+            .exec_iter(
+                q.get(
+                    "submit.tags",
+                    "SELECT  `tags`.* FROM `tags` \
+                     WHERE `tags`.`inactive` = 0 AND `tags`.`tag` IN ({tags})",
+                )
+                .replace("{tags}", &params),
+                tags,
+            )
+            .await?
+            .reduce_and_drop((Vec::new(), 0.0), |(mut tags, tags_mod), tag: Row| {
+                tags.push(tag.get::<u32, _>("id").unwrap());
+                (tags, tags_mod + ranking::hotness_mod(&tag))
+            })
+            .await?;
+
+        if !priming {
+            // check that story id isn't already assigned
+            c.exec_drop(
+                q.get(
+                    "submit.short_id_taken",
+                    "SELECT  1 AS one FROM `stories` \
+                     WHERE `stories`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&id[..]).unwrap(),),
+            )
+            .await?;
+        }
+
+        // check for previous submissions of the same url
+        if !priming {
+            if let Some(url) = &url {
+                let urls = urls::variants(url);
+                let params = urls.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+                let similar = c
+                    .exec_iter(
+                        q.get(
+                            "submit.similar_stories",
+                            "SELECT  `stories`.* \
+                             FROM `stories` \
+                             WHERE `stories`.`url` IN ({urls}) \
+                             AND (`stories`.`is_expired` = 0 OR `stories`.`is_moderated` = 1)",
+                        )
+                        .replace("{urls}", &params),
+                        urls,
+                    )
+                    .await?
+                    .reduce_and_drop(0, |n, _: Row| n + 1)
+                    .await?;
+
+                // the real site turns recent duplicates away, but trawler counts on every
+                // story it submits to exist, so they are only counted here
+                let outcome = if similar > 0 { "duplicate" } else { "new" };
+                counter!("lobsters_submitted_urls", "outcome" => outcome).increment(1);
+            }
+        }
+
+        // TODO
+        // real impl queries `tags` and `users` again here..?
+
+        // TODO: real impl checks *new* short_id and duplicate urls *again*
+
+        let description = lorem::paragraph(&mut rand::thread_rng());
+
+        // a new story only has its submitter's upvote
+        let now = chrono::Local::now().naive_local();
+        let mut hotness = Hotness {
+            upvotes: 1,
+            downvotes: 0,
+            created_at: now,
+            tags_mod,
+            authored_link: false,
+            comment_votes: Vec::new(),
+            merged_votes: Vec::new(),
+        };
+
+        // NOTE: MySQL technically does everything inside this and_then in a transaction,
+        // but let's be nice to it
+        let q_insert = c
+            .exec_iter(
+                q.get(
+                    "submit.insert_story",
+                    "INSERT INTO `stories` \
+                     (`created_at`, `user_id`, `url`, `title`, \
+                     `description`, `short_id`, `upvotes`, `hotness`, \
+                     `markeddown_description`) \
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                ),
+                (
+                    now,
+                    user,
+                    url,
+                    title,
+                    &description,
+                    ::std::str::from_utf8(&id[..]).unwrap(),
+                    1,
+                    hotness.compute(),
+                    format!("<p>{}</p>\n", description),
+                ),
+            )
+            .await?;
+        let story = q_insert.last_insert_id().unwrap();
+        q_insert.drop_result().await?;
+
+        for tag in tags {
+            c.exec_drop(
+                q.get(
+                    "submit.insert_tagging",
+                    "INSERT INTO `taggings` (`story_id`, `tag_id`) \
+                     VALUES (?, ?)",
+                ),
+                (story, tag),
+            )
+            .await?;
+        }
+
+        let key = format!("user:{}:stories_submitted", user);
+        c.exec_drop(
+            q.get(
+                "submit.update_stories_submitted",
+                "INSERT INTO keystores (`key`, `value`) \
+                 VALUES (?, ?) \
+                 ON DUPLICATE KEY UPDATE `keystores`.`value` = `keystores`.`value` + 1",
+            ),
+            (key, 1),
+        )
+        .await?;
+
+        if !priming {
+            let key = format!("user:{}:stories_submitted", user);
+            c.exec_drop(
+                q.get(
+                    "submit.stories_submitted",
+                    "SELECT  `keystores`.* \
+                     FROM `keystores` \
+                     WHERE `keystores`.`key` = ?",
+                ),
+                (key,),
+            )
+            .await?;
+
+            c.exec_drop(
+                q.get(
+                    "submit.user_vote",
+                    "SELECT  `votes`.* FROM `votes` \
+                     WHERE `votes`.`user_id` = ? \
+                     AND `votes`.`story_id` = ? \
+                     AND `votes`.`comment_id` IS NULL",
+                ),
+                (user, story),
+            )
+            .await?;
+        }
+
+        c.exec_drop(
+            q.get(
+                "submit.insert_vote",
+                "INSERT INTO `votes` (`user_id`, `story_id`, `vote`) \
+                 VALUES (?, ?, ?)",
+            ),
+            (user, story, 1),
+        )
+        .await?;
+
+        if !priming {
+            hotness.comment_votes = c
+                .exec_iter(
+                    q.get(
+                        "submit.comment_votes",
+                        "SELECT \
+                         `comments`.`upvotes`, \
+                         `comments`.`downvotes` \
+                         FROM `comments` \
+                         JOIN `stories` ON (`stories`.`id` = `comments`.`story_id`) \
+                         WHERE `comments`.`story_id` = ? \
+                         AND `comments`.`user_id` <> `stories`.`user_id`",
+                    ),
+                    (story,),
+                )
+                .await?
+                .map_and_drop(|comment: Row| ranking::votes(&comment))
+                .await?;
+
+            // why oh why is story hotness *updated* here?!
+            c.exec_drop(
+                q.get(
+                    "submit.update_hotness",
+                    "UPDATE `stories` \
+                     SET `hotness` = ? \
+                     WHERE `stories`.`id` = ?",
+                ),
+                (hotness.compute(), story),
+            )
+            .await?;
+        }
+
+        Ok((c, false))
+    }
+}
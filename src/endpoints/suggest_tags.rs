@@ -0,0 +1,172 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::BTreeSet;
+use std::future::Future;
+use trawler::{StoryId, UserId};
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user suggesting the story's tags plus another one. The tags that enough users suggest
+/// replace the story's tags, as if a moderator had edited them.
+pub(crate) struct SuggestTags {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) story: StoryId,
+    /// The name of the tag the user thinks is missing.
+    pub(crate) tag: String,
+    /// How many users need to suggest a tag for it to be applied.
+    pub(crate) quorum: u64,
+}
+
+#[async_trait]
+impl Page for SuggestTags {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let SuggestTags {
+            acting_as,
+            story,
+            tag,
+            quorum,
+        } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let story = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "suggest_tags.story",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&story[..]).unwrap(),),
+            )
+            .await?
+            .unwrap();
+        let author = story.get::<u32, _>("user_id").unwrap();
+        let story = story.get::<u32, _>("id").unwrap();
+
+        let current = c
+            .exec_iter(
+                q.get(
+                    "suggest_tags.taggings",
+                    "SELECT `taggings`.* FROM `taggings` \
+                     WHERE `taggings`.`story_id` = ?",
+                ),
+                (story,),
+            )
+            .await?
+            .reduce_and_drop(BTreeSet::new(), |mut tags, tagging: Row| {
+                tags.insert(tagging.get::<u32, _>("tag_id").unwrap());
+                tags
+            })
+            .await?;
+
+        let extra = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "suggest_tags.tag",
+                    "SELECT `tags`.* FROM `tags` \
+                     WHERE `tags`.`inactive` = 0 \
+                     AND `tags`.`tag` = ?",
+                ),
+                (tag,),
+            )
+            .await?;
+        let mut suggested = current.clone();
+        suggested.extend(extra.map(|tag| tag.get::<u32, _>("id").unwrap()));
+
+        // users only have one set of suggestions per story
+        c.exec_drop(
+            q.get(
+                "suggest_tags.delete_previous",
+                "DELETE FROM `suggested_taggings` \
+                 WHERE `suggested_taggings`.`story_id` = ? \
+                 AND `suggested_taggings`.`user_id` = ?",
+            ),
+            (story, user),
+        )
+        .await?;
+
+        for tag in &suggested {
+            c.exec_drop(
+                q.get(
+                    "suggest_tags.insert",
+                    "INSERT INTO `suggested_taggings` (`story_id`, `tag_id`, `user_id`) \
+                     VALUES (?, ?, ?)",
+                ),
+                (story, tag, user),
+            )
+            .await?;
+        }
+
+        let agreed = c
+            .exec_iter(
+                q.get(
+                    "suggest_tags.suggestions",
+                    "SELECT `suggested_taggings`.`tag_id`, COUNT(*) AS `count` \
+                     FROM `suggested_taggings` \
+                     WHERE `suggested_taggings`.`story_id` = ? \
+                     GROUP BY `suggested_taggings`.`tag_id`",
+                ),
+                (story,),
+            )
+            .await?
+            .reduce_and_drop(BTreeSet::new(), |mut agreed, suggestion: Row| {
+                if suggestion.get::<u64, _>("count").unwrap() >= quorum {
+                    agreed.insert(suggestion.get::<u32, _>("tag_id").unwrap());
+                }
+                agreed
+            })
+            .await?;
+
+        if agreed.is_empty() || agreed == current {
+            return Ok((c, false));
+        }
+
+        c.exec_drop(
+            q.get(
+                "suggest_tags.delete_taggings",
+                "DELETE FROM `taggings` WHERE `taggings`.`story_id` = ?",
+            ),
+            (story,),
+        )
+        .await?;
+
+        for tag in &agreed {
+            c.exec_drop(
+                q.get(
+                    "suggest_tags.insert_tagging",
+                    "INSERT INTO `taggings` (`story_id`, `tag_id`) \
+                     VALUES (?, ?)",
+                ),
+                (story, tag),
+            )
+            .await?;
+        }
+
+        let now = chrono::Local::now().naive_local();
+        c.exec_drop(
+            q.get(
+                "suggest_tags.insert_moderation",
+                "INSERT INTO `moderations` \
+                 (`created_at`, `updated_at`, `story_id`, `user_id`, `action`, \
+                 `is_from_suggestions`) \
+                 VALUES (?, ?, ?, ?, ?, 1)",
+            ),
+            (
+                now,
+                now,
+                story,
+                author,
+                format!("changed tags from {:?} to {:?}", current, agreed),
+            ),
+        )
+        .await?;
+
+        Ok((c, false))
+    }
+}
@@ -0,0 +1,136 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::{StoryId, UserId};
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// Most characters in a story title.
+const MAX_TITLE_LEN: usize = 150;
+
+/// A user suggesting a better title for a story. Once enough users agree on the same
+/// suggestion, the story gets that title, as if a moderator had edited it.
+pub(crate) struct SuggestTitle {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) story: StoryId,
+    /// What the user adds to the current title (e.g., the year it was published).
+    pub(crate) suffix: &'static str,
+    /// How many users need to suggest the same title for it to be applied.
+    pub(crate) quorum: u64,
+}
+
+#[async_trait]
+impl Page for SuggestTitle {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let SuggestTitle {
+            acting_as,
+            story,
+            suffix,
+            quorum,
+        } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let story = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "suggest_title.story",
+                    "SELECT `stories`.* \
+                     FROM `stories` \
+                     WHERE `stories`.`short_id` = ?",
+                ),
+                (::std::str::from_utf8(&story[..]).unwrap(),),
+            )
+            .await?
+            .unwrap();
+        let title = story.get::<String, _>("title").unwrap();
+        let author = story.get::<u32, _>("user_id").unwrap();
+        let story = story.get::<u32, _>("id").unwrap();
+        let suggestion = if title.ends_with(suffix) {
+            title.clone()
+        } else {
+            format!("{}{}", title, suffix)
+                .chars()
+                .take(MAX_TITLE_LEN)
+                .collect()
+        };
+
+        // users only have one suggestion per story
+        c.exec_drop(
+            q.get(
+                "suggest_title.delete_previous",
+                "DELETE FROM `suggested_titles` \
+                 WHERE `suggested_titles`.`story_id` = ? \
+                 AND `suggested_titles`.`user_id` = ?",
+            ),
+            (story, user),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "suggest_title.insert",
+                "INSERT INTO `suggested_titles` (`story_id`, `user_id`, `title`) \
+                 VALUES (?, ?, ?)",
+            ),
+            (story, user, &suggestion),
+        )
+        .await?;
+
+        let top = c
+            .exec_first::<(String, u64), _, _>(
+                q.get(
+                    "suggest_title.suggestions",
+                    "SELECT `suggested_titles`.`title`, COUNT(*) AS `count` \
+                     FROM `suggested_titles` \
+                     WHERE `suggested_titles`.`story_id` = ? \
+                     GROUP BY `suggested_titles`.`title` \
+                     ORDER BY `count` DESC \
+                     LIMIT 1",
+                ),
+                (story,),
+            )
+            .await?;
+        let new_title = match top {
+            Some((new_title, count)) if count >= quorum && new_title != title => new_title,
+            _ => return Ok((c, false)),
+        };
+
+        c.exec_drop(
+            q.get(
+                "suggest_title.apply",
+                "UPDATE `stories` \
+                 SET `stories`.`title` = ? \
+                 WHERE `stories`.`id` = ?",
+            ),
+            (&new_title, story),
+        )
+        .await?;
+
+        let now = chrono::Local::now().naive_local();
+        c.exec_drop(
+            q.get(
+                "suggest_title.insert_moderation",
+                "INSERT INTO `moderations` \
+                 (`created_at`, `updated_at`, `story_id`, `user_id`, `action`, \
+                 `is_from_suggestions`) \
+                 VALUES (?, ?, ?, ?, ?, 1)",
+            ),
+            (
+                now,
+                now,
+                story,
+                author,
+                format!("changed title from {:?} to {:?}", title, new_title),
+            ),
+        )
+        .await?;
+
+        Ok((c, false))
+    }
+}
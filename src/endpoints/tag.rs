@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::HashSet;
+use std::future::Future;
+use trawler::UserId;
+
+use super::{listing, Page, STORIES_PER_PAGE};
+use crate::queries::QueryCatalog;
+
+/// The `/t/<tag>` listing of the hottest stories with a given tag.
+pub(crate) struct Tag {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) tag: String,
+    /// Which page of the listing, starting at 0 for the first.
+    pub(crate) page: u32,
+}
+
+#[async_trait]
+impl Page for Tag {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Tag {
+            acting_as,
+            tag,
+            page,
+        } = self;
+        let mut c = c.await?;
+
+        let tag = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "tag.tag",
+                    "SELECT `tags`.* FROM `tags` \
+                     WHERE `tags`.`tag` = ?",
+                ),
+                (tag,),
+            )
+            .await?;
+        let tag = match tag {
+            Some(tag) => tag.get::<u32, _>("id").unwrap(),
+            None => {
+                return Ok((c, true));
+            }
+        };
+
+        let stories = c
+            .exec_iter(
+                q.variant("tag.stories"),
+                (tag, STORIES_PER_PAGE + 1, page * STORIES_PER_PAGE),
+            )
+            .await?;
+        let (users, stories) = stories
+            .reduce_and_drop(
+                (HashSet::new(), HashSet::new()),
+                |(mut users, mut stories), story: Row| {
+                    users.insert(story.get::<u32, _>("user_id").unwrap());
+                    stories.insert(story.get::<u32, _>("id").unwrap());
+                    (users, stories)
+                },
+            )
+            .await?;
+
+        // less popular tags may not have any stories (yet), and pages past the end of the
+        // listing are empty
+        if stories.is_empty() {
+            return Ok((c, true));
+        }
+
+        if let Some(uid) = acting_as {
+            c.exec_drop(
+                q.get(
+                    "tag.hidden_stories",
+                    "SELECT `hidden_stories`.`story_id` \
+                     FROM `hidden_stories` \
+                     WHERE `hidden_stories`.`user_id` = ?",
+                ),
+                (uid,),
+            )
+            .await?;
+        }
+        c = listing::stories(c, q, "tag", acting_as, &users, &stories).await?;
+
+        Ok((c, true))
+    }
+}
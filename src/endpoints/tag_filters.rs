@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use trawler::UserId;
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// A user saving the set of tags whose stories they do not want to see on the filters page.
+pub(crate) struct TagFilters {
+    pub(crate) acting_as: Option<UserId>,
+    /// The names of the tags to filter from now on.
+    pub(crate) tags: Vec<String>,
+}
+
+#[async_trait]
+impl Page for TagFilters {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let TagFilters { acting_as, tags } = self;
+        let mut c = c.await?;
+        let user = acting_as.unwrap();
+
+        let all_tags = c
+            .query_iter(q.get(
+                "tag_filters.tags",
+                "SELECT `tags`.* FROM `tags` \
+                 WHERE `tags`.`inactive` = 0",
+            ))
+            .await?
+            .reduce_and_drop(HashMap::new(), |mut all_tags, tag: Row| {
+                all_tags.insert(
+                    tag.get::<String, _>("tag").unwrap(),
+                    tag.get::<u32, _>("id").unwrap(),
+                );
+                all_tags
+            })
+            .await?;
+        let wanted = tags
+            .iter()
+            .filter_map(|tag| all_tags.get(tag).copied())
+            .collect::<HashSet<_>>();
+
+        let current = c
+            .exec_iter(
+                q.get(
+                    "tag_filters.user_filters",
+                    "SELECT `tag_filters`.* FROM `tag_filters` \
+                     WHERE `tag_filters`.`user_id` = ?",
+                ),
+                (user,),
+            )
+            .await?
+            .reduce_and_drop(HashSet::new(), |mut current, filter: Row| {
+                current.insert(filter.get::<u32, _>("tag_id").unwrap());
+                current
+            })
+            .await?;
+
+        let removed = current
+            .difference(&wanted)
+            .map(|id| format!("{}", id))
+            .collect::<Vec<_>>();
+        if !removed.is_empty() {
+            c.exec_drop(
+                q.get(
+                    "tag_filters.delete",
+                    "DELETE FROM `tag_filters` \
+                     WHERE `tag_filters`.`user_id` = ? \
+                     AND `tag_filters`.`tag_id` IN ({tags})",
+                )
+                .replace("{tags}", &removed.join(",")),
+                (user,),
+            )
+            .await?;
+        }
+
+        let now = chrono::Local::now().naive_local();
+        for tag in wanted.difference(&current) {
+            c.exec_drop(
+                q.get(
+                    "tag_filters.insert",
+                    "INSERT INTO `tag_filters` \
+                     (`created_at`, `updated_at`, `user_id`, `tag_id`) \
+                     VALUES (?, ?, ?, ?)",
+                ),
+                (now, now, user, tag),
+            )
+            .await?;
+        }
+
+        Ok((c, false))
+    }
+}
@@ -0,0 +1,138 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use trawler::UserId;
+
+use super::listing::ids_in;
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// The `/threads/<user>` page of the threads a user most recently commented in, each with its
+/// whole reply tree.
+pub(crate) struct Threads {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) uid: UserId,
+}
+
+#[async_trait]
+impl Page for Threads {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let Threads { acting_as, uid } = self;
+        let mut c = c.await?;
+
+        if !super::user_exists(&mut c, q, "threads.user", uid).await? {
+            return Ok((c, true));
+        }
+
+        let threads = c
+            .exec_iter(
+                q.get(
+                    "threads.thread_ids",
+                    "SELECT `comments`.`thread_id` \
+                     FROM `comments` \
+                     WHERE `comments`.`user_id` = ? \
+                     AND `comments`.`thread_id` IS NOT NULL \
+                     GROUP BY `comments`.`thread_id` \
+                     ORDER BY MAX(`comments`.`id`) DESC \
+                     LIMIT 20",
+                ),
+                (uid,),
+            )
+            .await?
+            .map_and_drop(|thread: Row| thread.get::<u32, _>("thread_id").unwrap())
+            .await?;
+
+        if threads.is_empty() {
+            return Ok((c, true));
+        }
+
+        let threads = ids_in(&threads);
+        let (roots, mut children, users, stories) = c
+            .query_iter(
+                q.get(
+                    "threads.comments",
+                    "SELECT `comments`.* \
+                     FROM `comments` \
+                     WHERE `comments`.`thread_id` IN ({threads}) \
+                     ORDER BY confidence DESC",
+                )
+                .replace("{threads}", &threads),
+            )
+            .await?
+            .reduce_and_drop(
+                (
+                    Vec::new(),
+                    HashMap::<u32, Vec<u32>>::new(),
+                    HashSet::new(),
+                    HashSet::new(),
+                ),
+                |(mut roots, mut children, mut users, mut stories), comment: Row| {
+                    let id = comment.get::<u32, _>("id").unwrap();
+                    match comment.get::<Option<u32>, _>("parent_comment_id").unwrap() {
+                        Some(parent) => children.entry(parent).or_default().push(id),
+                        None => roots.push(id),
+                    }
+                    users.insert(comment.get::<u32, _>("user_id").unwrap());
+                    stories.insert(comment.get::<u32, _>("story_id").unwrap());
+                    (roots, children, users, stories)
+                },
+            )
+            .await?;
+
+        // arrange each thread into its reply tree like the real site does before rendering;
+        // replies whose parent isn't part of the thread are left out
+        let mut comments = Vec::new();
+        let mut stack: Vec<_> = roots.into_iter().rev().collect();
+        while let Some(id) = stack.pop() {
+            comments.push(id);
+            if let Some(replies) = children.remove(&id) {
+                stack.extend(replies.into_iter().rev());
+            }
+        }
+
+        let users = ids_in(&users);
+        c.query_drop(
+            q.get(
+                "threads.users",
+                "SELECT `users`.* FROM `users` WHERE `users`.`id` IN ({users})",
+            )
+            .replace("{users}", &users),
+        )
+        .await?;
+
+        let stories = ids_in(&stories);
+        c.query_drop(
+            q.get(
+                "threads.stories",
+                "SELECT `stories`.* FROM `stories` \
+                 WHERE `stories`.`id` IN ({stories})",
+            )
+            .replace("{stories}", &stories),
+        )
+        .await?;
+
+        if let Some(uid) = acting_as {
+            if !comments.is_empty() {
+                let comments = ids_in(&comments);
+                c.exec_drop(
+                    q.get(
+                        "threads.votes",
+                        "SELECT `votes`.* FROM `votes` \
+                         WHERE `votes`.`user_id` = ? \
+                         AND `votes`.`comment_id` IN ({comments})",
+                    )
+                    .replace("{comments}", &comments),
+                    (uid,),
+                )
+                .await?;
+            }
+        }
+
+        Ok((c, true))
+    }
+}
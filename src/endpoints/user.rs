@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::future::Future;
+use trawler::UserId;
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+pub(crate) struct User {
+    pub(crate) acting_as: Option<UserId>,
+    pub(crate) uid: UserId,
+}
+
+#[async_trait]
+impl Page for User {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let User { acting_as: _, uid } = self;
+        let mut c = c.await?;
+        let user = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "user.user",
+                    "SELECT  `users`.* FROM `users` \
+                     WHERE `users`.`username` = ?",
+                ),
+                (format!("user{}", uid),),
+            )
+            .await?;
+        let uid = match user {
+            Some(uid) => uid.get::<u32, _>("id").unwrap(),
+            None => {
+                return Ok((c, false));
+            }
+        };
+
+        // most popular tag
+        let mut rows = c
+            .exec_iter(q.variant("user.top_tag"), (uid,))
+            .await?
+            .collect_and_drop::<Row>()
+            .await?;
+
+        // some variants only find the id of the tag, and look up the rest separately
+        if let Some(details) = q.optional("user.top_tag_details") {
+            if !rows.is_empty() {
+                let tag = rows.swap_remove(0);
+                c.exec_drop(details, (tag.get::<u32, _>("id").unwrap(),))
+                    .await?;
+            }
+        }
+
+        c.exec_drop(
+            q.get(
+                "user.stories_submitted",
+                "SELECT  `keystores`.* \
+                 FROM `keystores` \
+                 WHERE `keystores`.`key` = ?",
+            ),
+            (format!("user:{}:stories_submitted", uid),),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "user.comments_posted",
+                "SELECT  `keystores`.* \
+                 FROM `keystores` \
+                 WHERE `keystores`.`key` = ?",
+            ),
+            (format!("user:{}:comments_posted", uid),),
+        )
+        .await?;
+
+        c.exec_drop(
+            q.get(
+                "user.has_hats",
+                "SELECT  1 AS one FROM `hats` \
+                 WHERE `hats`.`user_id` = ? LIMIT 1",
+            ),
+            (uid,),
+        )
+        .await?;
+
+        Ok((c, true))
+    }
+}
@@ -0,0 +1,33 @@
+use async_trait::async_trait;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error};
+use std::future::Future;
+use trawler::UserId;
+
+use super::Page;
+use crate::queries::QueryCatalog;
+
+/// The `/users` page, which shows every user in the tree of who invited whom.
+pub(crate) struct UserTree {
+    pub(crate) acting_as: Option<UserId>,
+}
+
+#[async_trait]
+impl Page for UserTree {
+    async fn handle<F>(self, c: F, q: &QueryCatalog) -> Result<(Conn, bool), Error>
+    where
+        F: 'static + Future<Output = Result<Conn, Error>> + Send,
+    {
+        let UserTree { acting_as: _ } = self;
+        let mut c = c.await?;
+
+        // the real site loads every user and arranges them by inviter in memory
+        c.query_drop(q.get(
+            "user_tree.users",
+            "SELECT `users`.* FROM `users` ORDER BY `users`.`id` DESC",
+        ))
+        .await?;
+
+        Ok((c, true))
+    }
+}
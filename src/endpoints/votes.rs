@@ -0,0 +1,136 @@
+use metrics::counter;
+use mysql_async::Row;
+use trawler::Vote;
+
+/// What a vote request does, given the vote the user may already have cast on the same story
+/// or comment.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum VoteChange {
+    /// The user had not voted yet, and casts a vote.
+    New { up: bool },
+    /// The user votes the same way again, which changes nothing.
+    Noop,
+    /// The user votes the other way, which flips their existing vote.
+    Flip { up: bool },
+    /// The user withdraws their existing vote.
+    Unvote { up: bool },
+}
+
+impl VoteChange {
+    /// Decide what voting `v` does given the user's `existing` vote row. If `unvote` is set, the
+    /// user withdraws their existing vote instead (if they have one).
+    pub(crate) fn new(existing: Option<&Row>, v: Vote, unvote: bool) -> Self {
+        let existing = existing.map(|vote| vote.get::<i8, _>("vote").unwrap() == 1);
+        Self::given(existing, v, unvote)
+    }
+
+    /// Like [`VoteChange::new`], given only whether the existing vote (if any) was an upvote.
+    fn given(existing: Option<bool>, v: Vote, unvote: bool) -> Self {
+        let up = matches!(v, Vote::Up);
+        match existing {
+            None if unvote => VoteChange::Noop,
+            None => VoteChange::New { up },
+            Some(was_up) if unvote => VoteChange::Unvote { up: was_up },
+            Some(was_up) if was_up == up => VoteChange::Noop,
+            Some(_) => VoteChange::Flip { up },
+        }
+    }
+
+    /// The change in `(upvotes, downvotes)` of the story or comment voted on.
+    pub(crate) fn deltas(self) -> (i64, i64) {
+        match self {
+            VoteChange::New { up: true } => (1, 0),
+            VoteChange::New { up: false } => (0, 1),
+            VoteChange::Noop => (0, 0),
+            VoteChange::Flip { up: true } => (1, -1),
+            VoteChange::Flip { up: false } => (-1, 1),
+            VoteChange::Unvote { up: true } => (-1, 0),
+            VoteChange::Unvote { up: false } => (0, -1),
+        }
+    }
+
+    /// The change in karma of the author of the story or comment voted on.
+    pub(crate) fn karma(self) -> i64 {
+        let (up, down) = self.deltas();
+        up - down
+    }
+
+    /// Count this outcome for votes on `kind` (`story` or `comment`).
+    pub(crate) fn record(self, kind: &'static str) {
+        let outcome = match self {
+            VoteChange::New { .. } => "new",
+            VoteChange::Noop => "noop",
+            VoteChange::Flip { .. } => "flip",
+            VoteChange::Unvote { .. } => "unvote",
+        };
+        counter!("lobsters_votes", "kind" => kind, "outcome" => outcome).increment(1);
+    }
+}
+
+/// Render `n` for the `{upvotes}`, `{downvotes}` and `{karma}` placeholders (e.g. `+ 1`).
+pub(crate) fn delta(n: i64) -> String {
+    if n < 0 {
+        format!("- {}", -n)
+    } else {
+        format!("+ {}", n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_given_existing_vote() {
+        use VoteChange::*;
+
+        assert_eq!(VoteChange::given(None, Vote::Up, false), New { up: true });
+        assert_eq!(
+            VoteChange::given(None, Vote::Down, false),
+            New { up: false }
+        );
+        assert_eq!(VoteChange::given(None, Vote::Up, true), Noop);
+        assert_eq!(VoteChange::given(Some(true), Vote::Up, false), Noop);
+        assert_eq!(VoteChange::given(Some(false), Vote::Down, false), Noop);
+        assert_eq!(
+            VoteChange::given(Some(true), Vote::Down, false),
+            Flip { up: false }
+        );
+        assert_eq!(
+            VoteChange::given(Some(false), Vote::Up, false),
+            Flip { up: true }
+        );
+        assert_eq!(
+            VoteChange::given(Some(true), Vote::Down, true),
+            Unvote { up: true }
+        );
+        assert_eq!(
+            VoteChange::given(Some(false), Vote::Up, true),
+            Unvote { up: false }
+        );
+    }
+
+    #[test]
+    fn deltas_and_karma() {
+        let changes = [
+            (VoteChange::New { up: true }, (1, 0), 1),
+            (VoteChange::New { up: false }, (0, 1), -1),
+            (VoteChange::Noop, (0, 0), 0),
+            (VoteChange::Flip { up: true }, (1, -1), 2),
+            (VoteChange::Flip { up: false }, (-1, 1), -2),
+            (VoteChange::Unvote { up: true }, (-1, 0), -1),
+            (VoteChange::Unvote { up: false }, (0, -1), 1),
+        ];
+        for (change, deltas, karma) in changes {
+            assert_eq!(change.deltas(), deltas, "{:?}", change);
+            assert_eq!(change.karma(), karma, "{:?}", change);
+        }
+    }
+
+    #[test]
+    fn delta_placeholders() {
+        assert_eq!(delta(1), "+ 1");
+        assert_eq!(delta(0), "+ 0");
+        assert_eq!(delta(-2), "- 2");
+    }
+}
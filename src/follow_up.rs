@@ -0,0 +1,525 @@
+use mysql_async::{Conn, Error};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashSet;
+use std::future;
+use std::sync::{Arc, Mutex};
+use trawler::{LobstersRequest, UserId};
+
+use crate::endpoints;
+use crate::endpoints::delete_comment::DeleteComment;
+use crate::endpoints::doff_hat::DoffHat;
+use crate::endpoints::edit_comment::EditComment;
+use crate::endpoints::edit_story::EditStory;
+use crate::endpoints::feed::{Feed, FeedOf};
+use crate::endpoints::follow::Follow;
+use crate::endpoints::grant_hat::GrantHat;
+use crate::endpoints::hat_request::HatRequest;
+use crate::endpoints::hide::Hide;
+use crate::endpoints::inbox::Inbox;
+use crate::endpoints::invitation_request::InvitationRequest;
+use crate::endpoints::invite::Invite;
+use crate::endpoints::merge::Merge;
+use crate::endpoints::message::Message;
+use crate::endpoints::moderate::{Moderate, Moderation};
+use crate::endpoints::moderations::Moderations;
+use crate::endpoints::preferences::Preferences;
+use crate::endpoints::read_message::ReadMessage;
+use crate::endpoints::replies::Replies;
+use crate::endpoints::save::Save;
+use crate::endpoints::search::{Search, SearchWhat};
+use crate::endpoints::signup::Signup;
+use crate::endpoints::suggest_tags::SuggestTags;
+use crate::endpoints::suggest_title::SuggestTitle;
+use crate::endpoints::tag::Tag;
+use crate::endpoints::tag_filters::TagFilters;
+use crate::endpoints::threads::Threads;
+use crate::endpoints::user_tree::UserTree;
+use crate::endpoints::{self, Credentials, Page};
+use crate::lorem;
+use crate::queries::QueryCatalog;
+use crate::tags::TagVocabulary;
+use crate::Options;
+
+/// How far back among the recent stories a story may be merged into.
+const MERGE_TARGET_WINDOW: u32 = 50;
+
+/// Length of the random short ids of private messages.
+const MESSAGE_SHORT_ID_LEN: usize = 10;
+
+/// Length of the random codes of invitations and invitation requests.
+const INVITATION_CODE_LEN: usize = 15;
+
+/// Fraction of searches that are for comments rather than stories.
+const COMMENT_SEARCHES: f64 = 0.2;
+
+/// Chance that a user edits their tag filters down to none at all.
+const CLEAR_FILTERS: f64 = 0.2;
+
+/// Fraction of users that are primed with tag filters.
+const FILTERING_USERS: f64 = 0.2;
+
+/// Most stories a user is primed with as hidden, and as saved.
+const MAX_PRIMED_HIDDEN: u32 = 10;
+const MAX_PRIMED_SAVED: u32 = 10;
+
+/// The hats users ask for.
+const HATS: &[&str] = &[
+    "Project maintainer",
+    "Core contributor",
+    "Sysop",
+    "Speaking for my employer",
+    "Conference organizer",
+];
+
+/// What users suggest adding to story titles.
+const TITLE_SUFFIXES: &[&str] = &[" (2019)", " [pdf]", " [video]"];
+
+/// Fractions of feed polls that are for the frontpage and for the newest stories; the rest
+/// are for the stories of a user.
+const FRONTPAGE_FEEDS: f64 = 0.5;
+const NEWEST_FEEDS: f64 = 0.35;
+
+/// A page that trawler does not generate requests for, which the processor runs as a
+/// follow-up to one of the requests it does generate.
+pub(crate) enum FollowUp {
+    Merge(Merge),
+    Hide(Hide),
+    Save(Save),
+    TagFilters(TagFilters),
+    Preferences(Preferences),
+    Message(Message),
+    Inbox(Inbox),
+    ReadMessage(ReadMessage),
+    Search(Search),
+    Tag(Tag),
+    Threads(Threads),
+    Moderate(Moderate),
+    Moderations(Moderations),
+    EditComment(EditComment),
+    DeleteComment(DeleteComment),
+    EditStory(EditStory),
+    HatRequest(HatRequest),
+    GrantHat(GrantHat),
+    DoffHat(DoffHat),
+    InvitationRequest(InvitationRequest),
+    Invite(Invite),
+    Signup(Signup),
+    UserTree(UserTree),
+    SuggestTitle(SuggestTitle),
+    SuggestTags(SuggestTags),
+    Follow(Follow),
+    Replies(Replies),
+    Feed(Feed),
+}
+
+impl FollowUp {
+    /// The name the page's latencies are reported under.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            FollowUp::Merge(_) => "Merge",
+            FollowUp::Hide(_) => "Hide",
+            FollowUp::Save(_) => "Save",
+            FollowUp::TagFilters(_) => "TagFilters",
+            FollowUp::Preferences(_) => "Preferences",
+            FollowUp::Message(_) => "Message",
+            FollowUp::Inbox(_) => "Inbox",
+            FollowUp::ReadMessage(_) => "ReadMessage",
+            FollowUp::Search(_) => "Search",
+            FollowUp::Tag(_) => "Tag",
+            FollowUp::Threads(_) => "Threads",
+            FollowUp::Moderate(_) => "Moderate",
+            FollowUp::Moderations(_) => "Moderations",
+            FollowUp::EditComment(_) => "EditComment",
+            FollowUp::DeleteComment(_) => "DeleteComment",
+            FollowUp::EditStory(_) => "EditStory",
+            FollowUp::HatRequest(_) => "HatRequest",
+            FollowUp::GrantHat(_) => "GrantHat",
+            FollowUp::DoffHat(_) => "DoffHat",
+            FollowUp::InvitationRequest(_) => "InvitationRequest",
+            FollowUp::Invite(_) => "Invite",
+            FollowUp::Signup(_) => "Signup",
+            FollowUp::UserTree(_) => "UserTree",
+            FollowUp::SuggestTitle(_) => "SuggestTitle",
+            FollowUp::SuggestTags(_) => "SuggestTags",
+            FollowUp::Follow(_) => "Follow",
+            FollowUp::Replies(_) => "Replies",
+            FollowUp::Feed(Feed { of }) => match of {
+                FeedOf::Frontpage => "FrontpageFeed",
+                FeedOf::Newest => "NewestFeed",
+                FeedOf::User(_) => "UserFeed",
+            },
+        }
+    }
+
+    /// The moderator that runs the page, rather than the user whose request it follows.
+    pub(crate) fn moderator(&self) -> Option<UserId> {
+        match self {
+            FollowUp::Merge(page) => page.acting_as,
+            FollowUp::Moderate(page) => page.acting_as,
+            FollowUp::GrantHat(page) => page.acting_as,
+            _ => None,
+        }
+    }
+
+    /// Whether the page is requested by the acting user, who is then looked up by their session
+    /// and gets their notifications, as opposed to fetched anonymously by a feed reader.
+    pub(crate) fn is_by_user(&self) -> bool {
+        !matches!(self, FollowUp::Feed(_))
+    }
+
+    /// Run the page, returning the connection and whether the notifications for the acting
+    /// user should be loaded, too.
+    pub(crate) async fn handle(self, c: Conn, q: &QueryCatalog) -> Result<(Conn, bool), Error> {
+        let c = future::ready(Ok(c));
+        match self {
+            FollowUp::Merge(page) => page.handle(c, q).await,
+            FollowUp::Hide(page) => page.handle(c, q).await,
+            FollowUp::Save(page) => page.handle(c, q).await,
+            FollowUp::TagFilters(page) => page.handle(c, q).await,
+            FollowUp::Preferences(page) => page.handle(c, q).await,
+            FollowUp::Message(page) => page.handle(c, q).await,
+            FollowUp::Inbox(page) => page.handle(c, q).await,
+            FollowUp::ReadMessage(page) => page.handle(c, q).await,
+            FollowUp::Search(page) => page.handle(c, q).await,
+            FollowUp::Tag(page) => page.handle(c, q).await,
+            FollowUp::Threads(page) => page.handle(c, q).await,
+            FollowUp::Moderate(page) => page.handle(c, q).await,
+            FollowUp::Moderations(page) => page.handle(c, q).await,
+            FollowUp::EditComment(page) => page.handle(c, q).await,
+            FollowUp::DeleteComment(page) => page.handle(c, q).await,
+            FollowUp::EditStory(page) => page.handle(c, q).await,
+            FollowUp::HatRequest(page) => page.handle(c, q).await,
+            FollowUp::GrantHat(page) => page.handle(c, q).await,
+            FollowUp::DoffHat(page) => page.handle(c, q).await,
+            FollowUp::InvitationRequest(page) => page.handle(c, q).await,
+            FollowUp::Invite(page) => page.handle(c, q).await,
+            FollowUp::Signup(page) => page.handle(c, q).await,
+            FollowUp::UserTree(page) => page.handle(c, q).await,
+            FollowUp::SuggestTitle(page) => page.handle(c, q).await,
+            FollowUp::SuggestTags(page) => page.handle(c, q).await,
+            FollowUp::Follow(page) => page.handle(c, q).await,
+            FollowUp::Replies(page) => page.handle(c, q).await,
+            FollowUp::Feed(page) => page.handle(c, q).await,
+        }
+    }
+}
+
+/// The rates at which follow-up pages are mixed into the workload.
+#[derive(Clone, Debug)]
+pub(crate) struct FollowUps {
+    merge_rate: f64,
+    hide_rate: f64,
+    save_rate: f64,
+    tag_filter_rate: f64,
+    message_rate: f64,
+    inbox_rate: f64,
+    search_rate: f64,
+    tag_page_rate: f64,
+    next_page_rate: f64,
+    threads_rate: f64,
+    moderation_rate: f64,
+    moderation_log_rate: f64,
+    edit_rate: f64,
+    delete_rate: f64,
+    hat_request_rate: f64,
+    hat_grant_rate: f64,
+    hat_doff_rate: f64,
+    invitation_rate: f64,
+    user_tree_rate: f64,
+    suggest_rate: f64,
+    suggestion_quorum: u64,
+    follow_rate: f64,
+    replies_rate: f64,
+    feed_rate: f64,
+    tags: Arc<TagVocabulary>,
+    /// The users whose preferences have been primed already.
+    primed: Arc<Mutex<HashSet<UserId>>>,
+}
+
+impl FollowUps {
+    pub(crate) fn new(options: &Options, tags: Arc<TagVocabulary>) -> Self {
+        Self {
+            merge_rate: options.merge_rate,
+            hide_rate: options.hide_rate,
+            save_rate: options.save_rate,
+            tag_filter_rate: options.tag_filter_rate,
+            message_rate: options.message_rate,
+            inbox_rate: options.inbox_rate,
+            search_rate: options.search_rate,
+            tag_page_rate: options.tag_page_rate,
+            next_page_rate: options.next_page_rate,
+            threads_rate: options.threads_rate,
+            moderation_rate: options.moderation_rate,
+            moderation_log_rate: options.moderation_log_rate,
+            edit_rate: options.edit_rate,
+            delete_rate: options.delete_rate,
+            hat_request_rate: options.hat_request_rate,
+            hat_grant_rate: options.hat_grant_rate,
+            hat_doff_rate: options.hat_doff_rate,
+            invitation_rate: options.invitation_rate,
+            user_tree_rate: options.user_tree_rate,
+            suggest_rate: options.suggest_rate,
+            suggestion_quorum: options.suggestion_quorum,
+            follow_rate: options.follow_rate,
+            replies_rate: options.replies_rate,
+            feed_rate: options.feed_rate,
+            tags,
+            primed: Default::default(),
+        }
+    }
+
+    /// Pick the follow-up pages to run after `acting_as` made the request `req`.
+    pub(crate) fn pick(
+        &self,
+        req: &LobstersRequest,
+        acting_as: Option<UserId>,
+        priming: bool,
+    ) -> Vec<FollowUp> {
+        let mut rng = rand::thread_rng();
+        let mut follow_ups = Vec::new();
+
+        if priming {
+            // prime the preferences of each user the first time they show up
+            let Some(uid) = acting_as else {
+                return follow_ups;
+            };
+            if self.primed.lock().unwrap().insert(uid) {
+                let filters = if rng.gen_bool(FILTERING_USERS) {
+                    self.tags.pick_filters(&mut rng)
+                } else {
+                    Vec::new()
+                };
+                follow_ups.push(FollowUp::Preferences(Preferences {
+                    acting_as,
+                    filters,
+                    hidden: rng.gen_range(0..=MAX_PRIMED_HIDDEN),
+                    saved: rng.gen_range(0..=MAX_PRIMED_SAVED),
+                }));
+            }
+            return follow_ups;
+        }
+
+        // feed readers poll along with everyone else, one feed at a time
+        if rng.gen::<f64>() < self.feed_rate {
+            let p = rng.gen::<f64>();
+            let of = match acting_as {
+                _ if p < FRONTPAGE_FEEDS => FeedOf::Frontpage,
+                Some(uid) if p >= FRONTPAGE_FEEDS + NEWEST_FEEDS => FeedOf::User(uid),
+                _ => FeedOf::Newest,
+            };
+            follow_ups.push(FollowUp::Feed(Feed { of }));
+        }
+
+        // searching is something anyone browsing the listings may do
+        if matches!(req, LobstersRequest::Frontpage | LobstersRequest::Recent)
+            && rng.gen::<f64>() < self.search_rate
+        {
+            let what = if rng.gen_bool(COMMENT_SEARCHES) {
+                SearchWhat::Comments
+            } else {
+                SearchWhat::Stories
+            };
+            follow_ups.push(FollowUp::Search(Search {
+                acting_as,
+                what,
+                terms: lorem::terms(&mut rng),
+            }));
+        }
+
+        // readers get to tag pages from the tags on stories in the listings and on story pages
+        if matches!(
+            req,
+            LobstersRequest::Frontpage | LobstersRequest::Recent | LobstersRequest::Story(_)
+        ) && rng.gen::<f64>() < self.tag_page_rate
+        {
+            follow_ups.push(FollowUp::Tag(Tag {
+                acting_as,
+                tag: self.tags.pick_one(&mut rng),
+                page: endpoints::listing_page(&mut rng, self.next_page_rate),
+            }));
+        }
+
+        // anyone looking at a profile may go on to the user's threads
+        if let LobstersRequest::User(uid) = *req {
+            if rng.gen::<f64>() < self.threads_rate {
+                follow_ups.push(FollowUp::Threads(Threads { acting_as, uid }));
+            }
+        }
+
+        // the moderation log is public, and mostly visited from the frontpage
+        if matches!(req, LobstersRequest::Frontpage) && rng.gen::<f64>() < self.moderation_log_rate
+        {
+            follow_ups.push(FollowUp::Moderations(Moderations { acting_as }));
+        }
+
+        // people find their way in from the frontpage: they ask for an invitation, or sign up
+        // with one they got, and some look at who invited whom
+        if matches!(req, LobstersRequest::Frontpage) {
+            if rng.gen::<f64>() < self.invitation_rate {
+                let name = endpoints::token(&mut rng, 10);
+                follow_ups.push(FollowUp::InvitationRequest(InvitationRequest {
+                    acting_as,
+                    code: endpoints::token(&mut rng, INVITATION_CODE_LEN),
+                    name,
+                    memo: lorem::paragraph(&mut rng),
+                }));
+            }
+            if rng.gen::<f64>() < self.invitation_rate {
+                follow_ups.push(FollowUp::Signup(Signup {
+                    acting_as,
+                    username: format!("invitee{}", endpoints::token(&mut rng, 10)),
+                    credentials: Credentials::generate(&mut rng),
+                }));
+            }
+            if rng.gen::<f64>() < self.user_tree_rate {
+                follow_ups.push(FollowUp::UserTree(UserTree { acting_as }));
+            }
+        }
+
+        // the rest are things only logged-in users do
+        let Some(uid) = acting_as else {
+            return follow_ups;
+        };
+
+        match *req {
+            LobstersRequest::Story(id) => {
+                if rng.gen::<f64>() < self.merge_rate {
+                    // whichever moderator is around, not the user who happened by
+                    follow_ups.push(FollowUp::Merge(Merge {
+                        acting_as: Some(rng.gen_range(0..endpoints::MODERATORS)),
+                        story: id,
+                        target_offset: rng.gen_range(0..MERGE_TARGET_WINDOW),
+                    }));
+                }
+                if rng.gen::<f64>() < self.moderation_rate {
+                    let action = match rng.gen_range(0..3) {
+                        0 => Moderation::DeleteComment,
+                        1 => Moderation::ExpireStory,
+                        _ => Moderation::EditTitle(lorem::title(&mut rng)),
+                    };
+                    follow_ups.push(FollowUp::Moderate(Moderate {
+                        acting_as: Some(rng.gen_range(0..endpoints::MODERATORS)),
+                        story: id,
+                        action,
+                    }));
+                }
+                if rng.gen::<f64>() < self.suggest_rate {
+                    let follow_up = if rng.gen_bool(0.5) {
+                        FollowUp::SuggestTitle(SuggestTitle {
+                            acting_as,
+                            story: id,
+                            suffix: TITLE_SUFFIXES.choose(&mut rng).unwrap(),
+                            quorum: self.suggestion_quorum,
+                        })
+                    } else {
+                        FollowUp::SuggestTags(SuggestTags {
+                            acting_as,
+                            story: id,
+                            tag: self.tags.pick_one(&mut rng),
+                            quorum: self.suggestion_quorum,
+                        })
+                    };
+                    follow_ups.push(follow_up);
+                }
+                if rng.gen::<f64>() < self.follow_rate {
+                    follow_ups.push(FollowUp::Follow(Follow {
+                        acting_as,
+                        story: id,
+                    }));
+                }
+                if rng.gen::<f64>() < self.hide_rate {
+                    follow_ups.push(FollowUp::Hide(Hide {
+                        acting_as,
+                        story: id,
+                    }));
+                }
+                if rng.gen::<f64>() < self.save_rate {
+                    follow_ups.push(FollowUp::Save(Save {
+                        acting_as,
+                        story: id,
+                    }));
+                }
+            }
+            LobstersRequest::Frontpage => {
+                if rng.gen::<f64>() < self.tag_filter_rate {
+                    let tags = if rng.gen_bool(CLEAR_FILTERS) {
+                        Vec::new()
+                    } else {
+                        self.tags.pick_filters(&mut rng)
+                    };
+                    follow_ups.push(FollowUp::TagFilters(TagFilters { acting_as, tags }));
+                }
+                if rng.gen::<f64>() < self.inbox_rate {
+                    // users mostly go to their inbox to read what's new
+                    follow_ups.push(FollowUp::Inbox(Inbox { acting_as }));
+                    follow_ups.push(FollowUp::ReadMessage(ReadMessage { acting_as }));
+                }
+                if rng.gen::<f64>() < self.replies_rate {
+                    follow_ups.push(FollowUp::Replies(Replies { acting_as }));
+                }
+                if rng.gen::<f64>() < self.hat_request_rate {
+                    follow_ups.push(FollowUp::HatRequest(HatRequest {
+                        acting_as,
+                        hat: HATS.choose(&mut rng).unwrap().to_string(),
+                        comment: lorem::paragraph(&mut rng),
+                    }));
+                }
+                if rng.gen::<f64>() < self.hat_grant_rate {
+                    // a moderator working through the queue of hat requests
+                    follow_ups.push(FollowUp::GrantHat(GrantHat {
+                        acting_as: Some(rng.gen_range(0..endpoints::MODERATORS)),
+                        short_id: endpoints::token(&mut rng, MESSAGE_SHORT_ID_LEN),
+                    }));
+                }
+                if rng.gen::<f64>() < self.hat_doff_rate {
+                    follow_ups.push(FollowUp::DoffHat(DoffHat { acting_as }));
+                }
+                if rng.gen::<f64>() < self.invitation_rate {
+                    follow_ups.push(FollowUp::Invite(Invite {
+                        acting_as,
+                        code: endpoints::token(&mut rng, INVITATION_CODE_LEN),
+                    }));
+                }
+            }
+            // authors mostly fix up what they posted right after posting it
+            LobstersRequest::Comment { id, .. } => {
+                if rng.gen::<f64>() < self.edit_rate {
+                    follow_ups.push(FollowUp::EditComment(EditComment {
+                        acting_as,
+                        comment: id,
+                        text: lorem::paragraph(&mut rng),
+                    }));
+                }
+                if rng.gen::<f64>() < self.delete_rate {
+                    follow_ups.push(FollowUp::DeleteComment(DeleteComment {
+                        acting_as,
+                        comment: id,
+                    }));
+                }
+            }
+            LobstersRequest::Submit { id, .. } => {
+                if rng.gen::<f64>() < self.edit_rate {
+                    follow_ups.push(FollowUp::EditStory(EditStory {
+                        acting_as,
+                        story: id,
+                        title: lorem::title(&mut rng),
+                        description: lorem::paragraph(&mut rng),
+                    }));
+                }
+            }
+            LobstersRequest::User(recipient) if recipient != uid => {
+                if rng.gen::<f64>() < self.message_rate {
+                    follow_ups.push(FollowUp::Message(Message {
+                        acting_as,
+                        recipient,
+                        short_id: endpoints::token(&mut rng, MESSAGE_SHORT_ID_LEN),
+                    }));
+                }
+            }
+            _ => {}
+        }
+
+        follow_ups
+    }
+}
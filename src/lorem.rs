@@ -0,0 +1,133 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Words for generated text. They are all long enough to not be skipped by the fulltext
+/// indexes (InnoDB ignores words shorter than three characters by default).
+const WORDS: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "labore",
+    "dolore",
+    "magna",
+    "aliqua",
+    "enim",
+    "minim",
+    "veniam",
+    "quis",
+    "nostrud",
+    "exercitation",
+    "ullamco",
+    "laboris",
+    "nisi",
+    "aliquip",
+    "commodo",
+    "consequat",
+    "duis",
+    "aute",
+    "irure",
+    "reprehenderit",
+    "voluptate",
+    "velit",
+    "esse",
+    "cillum",
+    "fugiat",
+    "nulla",
+    "pariatur",
+    "excepteur",
+    "sint",
+    "occaecat",
+    "cupidatat",
+    "non",
+    "proident",
+    "sunt",
+    "culpa",
+    "officia",
+    "deserunt",
+    "mollit",
+    "anim",
+    "est",
+    "laborum",
+    "compiler",
+    "kernel",
+    "database",
+    "cache",
+    "latency",
+    "throughput",
+    "index",
+    "query",
+    "thread",
+    "socket",
+    "benchmark",
+    "memory",
+    "allocator",
+    "scheduler",
+    "protocol",
+    "parser",
+    "runtime",
+    "garbage",
+    "collector",
+    "lambda",
+    "monad",
+    "closure",
+    "pointer",
+    "mutex",
+    "channel",
+    "replica",
+    "consensus",
+    "shard",
+    "quorum",
+    "vector",
+    "register",
+];
+
+/// Most words in a sentence, and most sentences in a paragraph.
+const MAX_SENTENCE: usize = 16;
+const MAX_PARAGRAPH: usize = 6;
+
+/// Most words in a title.
+const MAX_TITLE: usize = 10;
+
+/// A few words to search for.
+pub(crate) fn terms<R: Rng>(rng: &mut R) -> String {
+    let n = rng.gen_range(1..=3);
+    words(rng, n)
+}
+
+/// A story title: a handful of words, capitalized.
+pub(crate) fn title<R: Rng>(rng: &mut R) -> String {
+    let n = rng.gen_range(3..=MAX_TITLE);
+    let mut title = words(rng, n);
+    title[..1].make_ascii_uppercase();
+    title
+}
+
+/// A paragraph of one or more sentences.
+pub(crate) fn paragraph<R: Rng>(rng: &mut R) -> String {
+    let n = rng.gen_range(1..=MAX_PARAGRAPH);
+    (0..n)
+        .map(|_| {
+            let n = rng.gen_range(4..=MAX_SENTENCE);
+            let mut sentence = words(rng, n);
+            sentence[..1].make_ascii_uppercase();
+            sentence.push('.');
+            sentence
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn words<R: Rng>(rng: &mut R, n: usize) -> String {
+    (0..n)
+        .map(|_| *WORDS.choose(rng).unwrap())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -1,6 +1,6 @@
 extern crate mysql_async as my;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use clap::{Parser, ValueEnum};
 use metrics::{histogram, Histogram};
@@ -9,10 +9,32 @@ use mysql_async::prelude::*;
 use mysql_async::{Conn, Opts, OptsBuilder, Pool, PoolConstraints, PoolOpts, Row};
 
 use std::collections::HashMap;
+use std::fs;
+use std::future;
 use std::mem;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use trawler::{LobstersRequest, RequestProcessor, TrawlerRequest};
+use tokio::task::JoinHandle;
+use trawler::{LobstersRequest, RequestProcessor, TrawlerRequest, UserId};
+
+use crate::endpoints::comment::Comment;
+use crate::endpoints::comment_vote::CommentVote;
+use crate::endpoints::comments::Comments;
+use crate::endpoints::expire_stories::ExpireStories;
+use crate::endpoints::frontpage::Frontpage;
+use crate::endpoints::recent::Recent;
+use crate::endpoints::story::Story;
+use crate::endpoints::story_vote::StoryVote;
+use crate::endpoints::submit::Submit;
+use crate::endpoints::user::User;
+use crate::endpoints::{Credentials, Page, QueryProvider};
+use crate::follow_up::{FollowUp, FollowUps};
+use crate::queries::QueryCatalog;
+use crate::sessions::Sessions;
+use crate::tags::TagVocabulary;
+use crate::urls::Urls;
+use crate::users::{ActingUsers, UserDistribution};
 
 const ORIGINAL_SCHEMA: &str = include_str!("db-schema/original.sql");
 const NORIA_SCHEMA: &str = include_str!("db-schema/noria.sql");
@@ -21,6 +43,13 @@ const NORIA_SCHEMA: &str = include_str!("db-schema/noria.sql");
 const PUSH_GATEWAY_PUSH_INTERVAL: Duration = Duration::from_secs(5);
 
 mod endpoints;
+mod follow_up;
+mod lorem;
+mod queries;
+mod sessions;
+mod tags;
+mod urls;
+mod users;
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug, ValueEnum)]
 enum Variant {
@@ -34,7 +63,24 @@ struct MysqlTrawler {
     opts: OptsBuilder,
     pool: Option<Pool>,
     variant: Variant,
-    pages_histos: HashMap<String, Histogram>,
+    schema: Option<String>,
+    queries: Arc<QueryCatalog>,
+    tags: Arc<TagVocabulary>,
+    follow_ups: FollowUps,
+    sessions: Sessions,
+    urls: Urls,
+    users: ActingUsers,
+    unvote_rate: f64,
+    hat_rate: f64,
+    next_page_rate: f64,
+    anonymous_rate: f64,
+    pages_histos: HashMap<(String, bool), Histogram>,
+    /// The follow-up pages that are still running, or haven't been checked for errors yet.
+    running_follow_ups: Arc<Mutex<Vec<JoinHandle<Result<()>>>>>,
+    expire_after: Option<chrono::Duration>,
+    expire_interval: Duration,
+    /// Expiring stories, once the run is under way.
+    expiry: Arc<Mutex<Option<JoinHandle<Result<()>>>>>,
 }
 
 impl MysqlTrawler {
@@ -52,26 +98,144 @@ impl MysqlTrawler {
                     )
                     .with_reset_connection(false),
             );
+        let schema = match options.schema {
+            Some(ref path) => {
+                Some(fs::read_to_string(path).with_context(|| format!("reading schema {}", path))?)
+            }
+            None => None,
+        };
+        let provider: &dyn QueryProvider = match options.queries {
+            Variant::Original => &endpoints::original::Original,
+            Variant::Noria => &endpoints::noria::Noria,
+            // Variant::Natural => &endpoints::natural::Natural,
+        };
+        let mut queries = QueryCatalog::new(provider);
+        if let Some(ref path) = options.query_catalog {
+            queries.load_overrides(path, options.queries)?;
+        }
+        let tags = Arc::new(TagVocabulary::load(
+            options.tags.as_deref(),
+            options.tag_skew,
+        )?);
         let pool = Pool::new(opts.clone());
         Ok(Self {
             opts,
             pool: Some(pool),
             variant: options.queries,
+            schema,
+            queries: Arc::new(queries),
+            follow_ups: FollowUps::new(&options, Arc::clone(&tags)),
+            tags,
+            sessions: Default::default(),
+            urls: Urls::new(options.url_rate, options.duplicate_url_rate),
+            users: ActingUsers::new(
+                options.user_distribution,
+                options.user_skew,
+                options.active_users,
+            )?,
+            unvote_rate: options.unvote_rate,
+            hat_rate: options.hat_rate,
+            next_page_rate: options.next_page_rate,
+            anonymous_rate: options.anonymous_rate,
             pages_histos: Default::default(),
+            running_follow_ups: Default::default(),
+            expire_after: options
+                .expire_after
+                .map(|secs| chrono::Duration::seconds(secs as i64)),
+            expire_interval: Duration::from_secs(options.expire_interval),
+            expiry: Default::default(),
         })
     }
 
-    fn record_histo(&mut self, page_name: String, elaped: Duration) {
+    /// Record how long a page took, labeled by whether a logged-in user requested it.
+    fn record_histo(&mut self, page_name: String, logged_in: bool, elaped: Duration) {
+        let auth = if logged_in { "user" } else { "anon" };
         let histo = self
             .pages_histos
-            .entry(page_name.clone())
+            .entry((page_name.clone(), logged_in))
             .or_insert_with(|| {
-                let labels = vec![("page", page_name)];
+                let labels = vec![("page", page_name), ("auth", auth.to_string())];
                 histogram!("lobsters_page", &labels)
             })
             .clone();
         histo.record(elaped);
     }
+
+    /// Run the follow-up pages of a request by `acting_as` on `c`, one after the other.
+    async fn follow_up(
+        &mut self,
+        mut c: Conn,
+        follow_ups: Vec<FollowUp>,
+        acting_as: Option<UserId>,
+        priming: bool,
+    ) -> Result<()> {
+        let queries = Arc::clone(&self.queries);
+        let q = &*queries;
+        for follow_up in follow_ups {
+            let page_name = follow_up.name().to_string();
+            let timer = Instant::now();
+            let with_notifications;
+            let is_by_user = follow_up.is_by_user();
+            let acting_as = follow_up.moderator().or(acting_as);
+            if let Some(uid) = acting_as {
+                if is_by_user && !priming {
+                    (c, _) = self.sessions.authenticate(c, q, uid).await?;
+                }
+            }
+            (c, with_notifications) = follow_up.handle(c, q).await?;
+            if let Some(uid) = acting_as {
+                if is_by_user && with_notifications {
+                    c = endpoints::notifications(c, q, uid).await?;
+                }
+            }
+            self.record_histo(
+                page_name,
+                is_by_user && acting_as.is_some(),
+                timer.elapsed(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Start expiring stories every `expire_interval` on a connection of its own, unless stories
+    /// don't expire by age or that has been started already.
+    fn start_expiry(&self) {
+        let Some(max_age) = self.expire_after else {
+            return;
+        };
+        let mut expiry = self.expiry.lock().unwrap();
+        if expiry.is_some() {
+            return;
+        }
+
+        let mut this = self.clone();
+        let expiring: JoinHandle<Result<()>> = tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(this.expire_interval);
+            // the first tick is right away, but stories are only due once an interval is up
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let timer = Instant::now();
+                let c = this.pool.as_ref().expect("asdf").get_conn().await?;
+                let queries = Arc::clone(&this.queries);
+                ExpireStories { max_age }
+                    .handle(future::ready(Ok(c)), &queries)
+                    .await?;
+                this.record_histo("ExpireStories".to_string(), false, timer.elapsed());
+            }
+        });
+        *expiry = Some(expiring);
+    }
+
+    /// Take the follow-ups that have finished running off the list of running ones.
+    fn finished_follow_ups(&self) -> Vec<JoinHandle<Result<()>>> {
+        let mut running = self.running_follow_ups.lock().unwrap();
+        let (finished, still_running) = mem::take(&mut *running)
+            .into_iter()
+            .partition(|follow_ups: &JoinHandle<_>| follow_ups.is_finished());
+        *running = still_running;
+        finished
+    }
 }
 
 #[async_trait]
@@ -93,24 +257,26 @@ impl RequestProcessor for MysqlTrawler {
         c.query_drop(&db_drop).await?;
         c.query_drop(&db_create).await?;
         c.query_drop(&db_use).await?;
-        let schema = match self.variant {
-            Variant::Original => ORIGINAL_SCHEMA,
-            Variant::Noria => NORIA_SCHEMA,
-            //                    Variant::Natural => NATURAL_SCHEMA,
+        let schema = match (&self.schema, self.variant) {
+            (Some(schema), _) => schema.as_str(),
+            (None, Variant::Original) => ORIGINAL_SCHEMA,
+            (None, Variant::Noria) => NORIA_SCHEMA,
+            //                    (None, Variant::Natural) => NATURAL_SCHEMA,
         };
-        let mut current_q = String::new();
-        for line in schema.lines() {
-            if line.starts_with("--") || line.is_empty() {
-                continue;
-            }
-            if !current_q.is_empty() {
-                current_q.push(' ');
-            }
-            current_q.push_str(line);
-            if current_q.ends_with(';') {
-                c.query_drop(&current_q).await?;
-                current_q.clear();
-            }
+        for q in queries::statements(schema) {
+            c.query_drop(&q).await?;
+        }
+
+        for tag in self.tags.tags() {
+            c.exec_drop(
+                self.queries.get(
+                    "prime.insert_tag",
+                    "INSERT INTO `tags` (`tag`, `hotness_mod`, `is_media`, `privileged`) \
+                     VALUES (?, ?, ?, ?)",
+                ),
+                (&tag.tag, tag.hotness_mod, tag.is_media, tag.privileged),
+            )
+            .await?;
         }
 
         Ok(())
@@ -119,7 +285,7 @@ impl RequestProcessor for MysqlTrawler {
     async fn process(
         &mut self,
         TrawlerRequest {
-            user: acting_as,
+            user: mut acting_as,
             page: req,
             is_priming: priming,
             ..
@@ -134,89 +300,242 @@ impl RequestProcessor for MysqlTrawler {
         // really?!? how can it be this hard to get a name from the page enum?
         let page_name = LobstersRequest::variant_name(&mem::discriminant(&req)).to_string();
 
-        macro_rules! handle_req {
-            ($module:tt, $req:expr) => {{
-                match req {
-                    LobstersRequest::User(uid) => {
-                        endpoints::$module::user::handle(c, acting_as, uid).await
-                    }
-                    LobstersRequest::Frontpage => {
-                        endpoints::$module::frontpage::handle(c, acting_as).await
-                    }
-                    LobstersRequest::Comments => {
-                        endpoints::$module::comments::handle(c, acting_as).await
-                    }
-                    LobstersRequest::Recent => {
-                        endpoints::$module::recent::handle(c, acting_as).await
-                    }
-                    LobstersRequest::Login => {
-                        let mut c = c.await?;
-                        let user = c
-                            .exec_first::<Row, _, _>(
-                                "SELECT 1 as one FROM `users` WHERE `users`.`username` = ?",
-                                (format!("user{}", acting_as.unwrap()),),
-                            )
-                            .await?;
-
-                        if user.is_none() {
-                            let uid = acting_as.unwrap();
-                            c.exec_drop(
-                                "INSERT INTO `users` (`username`) VALUES (?)",
-                                (format!("user{}", uid),),
-                            )
-                            .await?;
-                        }
-
-                        Ok((c, false))
-                    }
-                    LobstersRequest::Logout => Ok((c.await?, false)),
-                    LobstersRequest::Story(id) => {
-                        endpoints::$module::story::handle(c, acting_as, id).await
-                    }
-                    LobstersRequest::StoryVote(story, v) => {
-                        endpoints::$module::story_vote::handle(c, acting_as, story, v).await
-                    }
-                    LobstersRequest::CommentVote(comment, v) => {
-                        endpoints::$module::comment_vote::handle(c, acting_as, comment, v).await
-                    }
-                    LobstersRequest::Submit { id, title } => {
-                        endpoints::$module::submit::handle(c, acting_as, id, title, priming).await
-                    }
-                    LobstersRequest::Comment { id, story, parent } => {
-                        endpoints::$module::comment::handle(
-                            c, acting_as, id, story, parent, priming,
-                        )
-                        .await
-                    }
-                }
-            }};
+        // some of the users reading pages are made to read them logged out instead
+        let is_read = matches!(
+            req,
+            LobstersRequest::Frontpage
+                | LobstersRequest::Recent
+                | LobstersRequest::Comments
+                | LobstersRequest::Story(_)
+                | LobstersRequest::User(_)
+        );
+        if is_read && !priming && rand::random::<f64>() < self.anonymous_rate {
+            acting_as = None;
+        }
+
+        // and the others may be made by another user than the one trawler picked, except for
+        // logging in and out, which is up to trawler's users
+        if let Some(uid) = acting_as {
+            if !priming && !matches!(req, LobstersRequest::Login | LobstersRequest::Logout) {
+                let pool = self.pool.as_ref().expect("asdf"); // just checked
+                self.users.seed(pool, &self.queries).await?;
+                acting_as = Some(self.users.remap(&mut rand::thread_rng(), uid));
+            }
+        }
+
+        if !priming {
+            self.start_expiry();
         }
 
+        // pick these before the request is consumed by the match below
+        let follow_ups = self.follow_ups.pick(&req, acting_as, priming);
+
+        // which page of the listing, for the pages that are listings
+        let page = endpoints::listing_page(&mut rand::thread_rng(), self.next_page_rate);
+
         let timer = Instant::now();
-        let variant = self.variant;
+        let queries = Arc::clone(&self.queries);
+        let q = &*queries;
 
-        let (c, with_notifications) = match variant {
-            Variant::Original => handle_req!(original, req),
-            Variant::Noria => handle_req!(noria, req),
-            // Variant::Natural => handle_req!(natural, req),
+        // every page a logged-in user requests starts by finding them by their session cookie
+        let mut c = c.await?;
+        let mut user = None;
+        if let Some(uid) = acting_as {
+            if !priming && !matches!(req, LobstersRequest::Login) {
+                (c, user) = self.sessions.authenticate(c, q, uid).await?;
+            }
+        }
+        let c = future::ready(Ok::<_, mysql_async::Error>(c));
+
+        let (mut c, with_notifications) = match req {
+            LobstersRequest::User(uid) => User { acting_as, uid }.handle(c, q).await,
+            LobstersRequest::Frontpage => Frontpage { acting_as, page }.handle(c, q).await,
+            LobstersRequest::Comments => Comments { acting_as, page }.handle(c, q).await,
+            LobstersRequest::Recent => Recent { acting_as, page }.handle(c, q).await,
+            LobstersRequest::Login => {
+                let mut c = c.await?;
+                let uid = acting_as.unwrap();
+                let user = c
+                    .exec_first::<Row, _, _>(
+                        q.get(
+                            "login.user",
+                            "SELECT `users`.* FROM `users` WHERE `users`.`username` = ?",
+                        ),
+                        (format!("user{}", uid),),
+                    )
+                    .await?;
+
+                let token = if let Some(user) = user {
+                    let token = user.get::<String, _>("session_token").unwrap();
+                    if token.is_empty() {
+                        let token = endpoints::token(&mut rand::thread_rng(), 60);
+                        c.exec_drop(
+                            q.get(
+                                "login.update_session_token",
+                                "UPDATE `users` \
+                                 SET `users`.`session_token` = ? \
+                                 WHERE `users`.`id` = ?",
+                            ),
+                            (&token, user.get::<u32, _>("id").unwrap()),
+                        )
+                        .await?;
+                        token
+                    } else {
+                        token
+                    }
+                } else {
+                    let credentials = Credentials::generate(&mut rand::thread_rng());
+                    c.exec_drop(
+                        q.get(
+                            "login.create_user",
+                            "INSERT INTO `users` \
+                             (`username`, `email`, `password_digest`, `created_at`, \
+                             `session_token`, `rss_token`, `mailing_list_token`, \
+                             `is_moderator`) \
+                             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                        ),
+                        (
+                            format!("user{}", uid),
+                            format!("user{}@example.com", uid),
+                            credentials.password_digest,
+                            chrono::Local::now().naive_local(),
+                            &credentials.session_token,
+                            credentials.rss_token,
+                            credentials.mailing_list_token,
+                            uid < endpoints::MODERATORS,
+                        ),
+                    )
+                    .await?;
+                    credentials.session_token
+                };
+                self.sessions.log_in(uid, token);
+
+                Ok((c, false))
+            }
+            LobstersRequest::Logout => {
+                let mut c = c.await?;
+                // logging out rolls the session token, so the old cookie stops working
+                if let Some(user) = user {
+                    let token = endpoints::token(&mut rand::thread_rng(), 60);
+                    c.exec_drop(
+                        q.get(
+                            "logout.rotate_session_token",
+                            "UPDATE `users` \
+                             SET `users`.`session_token` = ? \
+                             WHERE `users`.`id` = ?",
+                        ),
+                        (token, user),
+                    )
+                    .await?;
+                }
+                if let Some(uid) = acting_as {
+                    self.sessions.log_out(uid);
+                }
+                Ok((c, false))
+            }
+            LobstersRequest::Story(id) => Story { acting_as, id }.handle(c, q).await,
+            LobstersRequest::StoryVote(story, vote) => {
+                StoryVote {
+                    acting_as,
+                    story,
+                    vote,
+                    unvote: rand::random::<f64>() < self.unvote_rate,
+                }
+                .handle(c, q)
+                .await
+            }
+            LobstersRequest::CommentVote(comment, vote) => {
+                CommentVote {
+                    acting_as,
+                    comment,
+                    vote,
+                    unvote: rand::random::<f64>() < self.unvote_rate,
+                }
+                .handle(c, q)
+                .await
+            }
+            LobstersRequest::Submit { id, title } => {
+                let moderator = acting_as.is_some_and(|uid| uid < endpoints::MODERATORS);
+                let tags = self.tags.pick(&mut rand::thread_rng(), moderator);
+                let url = self.urls.pick(&mut rand::thread_rng());
+                Submit {
+                    acting_as,
+                    id,
+                    title,
+                    tags,
+                    url,
+                    priming,
+                }
+                .handle(c, q)
+                .await
+            }
+            LobstersRequest::Comment { id, story, parent } => {
+                Comment {
+                    acting_as,
+                    id,
+                    story,
+                    parent,
+                    hat: rand::random::<f64>() < self.hat_rate,
+                    priming,
+                }
+                .handle(c, q)
+                .await
+            }
         }?;
 
         // notifications
         if let Some(uid) = acting_as {
             if with_notifications && !priming {
-                match variant {
-                    Variant::Original => endpoints::original::notifications(c, uid).await,
-                    Variant::Noria => endpoints::noria::notifications(c, uid).await,
-                    // Variant::Natural => endpoints::natural::notifications(c, uid).await,
-                }?;
+                c = endpoints::notifications(c, q, uid).await?;
             }
         };
 
-        self.record_histo(page_name, timer.elapsed());
+        self.record_histo(page_name, acting_as.is_some(), timer.elapsed());
+
+        // follow-ups are timed as pages of their own, so outside of priming they run on a
+        // connection of their own, too, rather than adding to the time trawler takes for this
+        // request. priming runs them right away, so they are all done when the run starts
+        if priming {
+            self.follow_up(c, follow_ups, acting_as, priming).await?;
+        } else if !follow_ups.is_empty() {
+            drop(c);
+            let mut this = self.clone();
+            let running = tokio::spawn(async move {
+                let c = this.pool.as_ref().expect("asdf").get_conn().await?;
+                this.follow_up(c, follow_ups, acting_as, priming).await
+            });
+            self.running_follow_ups.lock().unwrap().push(running);
+        }
+
+        for finished in self.finished_follow_ups() {
+            finished.await??;
+        }
         Ok(())
     }
 
     async fn shutdown(&mut self) -> Result<()> {
+        // expiring stories goes on until it is stopped, unless it failed
+        let expiry = self.expiry.lock().unwrap().take();
+        if let Some(expiry) = expiry {
+            expiry.abort();
+            if let Ok(expired) = expiry.await {
+                expired?;
+            }
+        }
+        let running = mem::take(&mut *self.running_follow_ups.lock().unwrap());
+        for follow_ups in running {
+            follow_ups.await??;
+        }
+        for name in self.queries.unused_overrides() {
+            eprintln!(
+                "shutdown: the override of query {} was never issued; is its name misspelled?",
+                name
+            );
+        }
+        println!(
+            "shutdown: requests were made by {} distinct users",
+            self.users.distinct()
+        );
         if let Some(pool) = self.pool.take() {
             pool.disconnect().await?
         }
@@ -242,6 +561,176 @@ struct Options {
     #[arg(long, default_value = "noria")]
     queries: Variant,
 
+    /// Schema file to prime the database with, instead of the built-in schema for `queries`.
+    #[arg(long)]
+    schema: Option<String>,
+
+    /// File of named queries that override the built-in SQL for individual queries.
+    ///
+    /// Each entry is a `-- query: <page>.<step>` line, an optional `-- variant: <variant>`
+    /// line, and the SQL to issue, terminated by `;`.
+    #[arg(long)]
+    query_catalog: Option<String>,
+
+    /// File with the tag vocabulary to seed when priming, most popular tag first.
+    ///
+    /// Each line is `<tag> <hotness_mod>`, optionally followed by `media` and/or `privileged`.
+    #[arg(long)]
+    tags: Option<String>,
+
+    /// How strongly submissions favor the more popular tags (the Zipf exponent of tag ranks).
+    #[arg(long, default_value = "1.0")]
+    tag_skew: f64,
+
+    /// Fraction of logged-in story page views after which a moderator merges that story
+    /// into another recent one.
+    #[arg(long, default_value = "0.005")]
+    merge_rate: f64,
+
+    /// Fraction of logged-in story page views after which the user hides (or unhides) the story.
+    #[arg(long, default_value = "0.01")]
+    hide_rate: f64,
+
+    /// Fraction of logged-in story page views after which the user saves (or unsaves) the story.
+    #[arg(long, default_value = "0.01")]
+    save_rate: f64,
+
+    /// Fraction of logged-in frontpage views after which the user edits their tag filters.
+    #[arg(long, default_value = "0.001")]
+    tag_filter_rate: f64,
+
+    /// Fraction of logged-in views of another user's profile after which the viewer sends them
+    /// a private message.
+    #[arg(long, default_value = "0.05")]
+    message_rate: f64,
+
+    /// Fraction of logged-in frontpage views after which the user checks their inbox and reads
+    /// their oldest unread message.
+    #[arg(long, default_value = "0.02")]
+    inbox_rate: f64,
+
+    /// Fraction of frontpage and recent views after which the user searches stories or comments.
+    #[arg(long, default_value = "0.01")]
+    search_rate: f64,
+
+    /// Fraction of frontpage, recent and story views after which the user browses a tag page.
+    #[arg(long, default_value = "0.1")]
+    tag_page_rate: f64,
+
+    /// Fraction of user profile views after which the viewer goes on to the user's threads.
+    #[arg(long, default_value = "0.2")]
+    threads_rate: f64,
+
+    /// Fraction of logged-in story page views after which a moderator deletes a comment on the
+    /// story, expires the story, or edits its title.
+    #[arg(long, default_value = "0.005")]
+    moderation_rate: f64,
+
+    /// Fraction of frontpage views after which the user browses the moderation log.
+    #[arg(long, default_value = "0.005")]
+    moderation_log_rate: f64,
+
+    /// Fraction of new comments and stories that their author edits right after posting.
+    #[arg(long, default_value = "0.05")]
+    edit_rate: f64,
+
+    /// Fraction of new comments that their author deletes right after posting.
+    #[arg(long, default_value = "0.01")]
+    delete_rate: f64,
+
+    /// Fraction of logged-in frontpage views after which the user asks for a hat.
+    #[arg(long, default_value = "0.002")]
+    hat_request_rate: f64,
+
+    /// Fraction of logged-in frontpage views after which a moderator grants the oldest pending
+    /// hat request.
+    #[arg(long, default_value = "0.002")]
+    hat_grant_rate: f64,
+
+    /// Fraction of logged-in frontpage views after which the user doffs one of their hats.
+    #[arg(long, default_value = "0.0002")]
+    hat_doff_rate: f64,
+
+    /// Fraction of comments whose author wears one of their hats for it, if they have any.
+    #[arg(long, default_value = "0.2")]
+    hat_rate: f64,
+
+    /// Fraction of frontpage views after which a visitor asks for an invitation, and, equally
+    /// often, one signs up with an invitation and a logged-in user sends one.
+    #[arg(long, default_value = "0.001")]
+    invitation_rate: f64,
+
+    /// Fraction of frontpage views after which the user browses the tree of all users.
+    #[arg(long, default_value = "0.0005")]
+    user_tree_rate: f64,
+
+    /// Fraction of submitted stories that link to a URL rather than being text posts.
+    #[arg(long, default_value = "0.7")]
+    url_rate: f64,
+
+    /// Fraction of URL submissions that link to a recently submitted URL again.
+    #[arg(long, default_value = "0.05")]
+    duplicate_url_rate: f64,
+
+    /// Fraction of logged-in story page views after which the user suggests a title or tags.
+    #[arg(long, default_value = "0.01")]
+    suggest_rate: f64,
+
+    /// How many users need to suggest the same title or tag for it to be applied.
+    #[arg(long, default_value = "3")]
+    suggestion_quorum: u64,
+
+    /// Fraction of logged-in story page views after which the user unfollows the story, or
+    /// follows it again.
+    #[arg(long, default_value = "0.01")]
+    follow_rate: f64,
+
+    /// Fraction of logged-in frontpage views after which the user reads their unread replies.
+    #[arg(long, default_value = "0.05")]
+    replies_rate: f64,
+
+    /// Fraction of requests that are accompanied by a feed reader polling one of the RSS or
+    /// JSON feeds.
+    #[arg(long, default_value = "0.05")]
+    feed_rate: f64,
+
+    /// Expire stories once they are this many seconds old. Stories are never expired by age
+    /// if not set.
+    #[arg(long)]
+    expire_after: Option<u64>,
+
+    /// How often, in seconds, to look for stories to expire.
+    #[arg(long, default_value = "10")]
+    expire_interval: u64,
+
+    /// Fraction of views of a page of the frontpage, `/recent`, `/comments` or a tag that go on
+    /// to the next page instead, so that page numbers follow a geometric distribution. Must be below 1.
+    #[arg(long, default_value = "0.1")]
+    next_page_rate: f64,
+
+    /// Fraction of the frontpage, `/recent`, `/comments`, story and user page views by
+    /// logged-in users that are run anonymously instead, follow-ups included.
+    #[arg(long, default_value = "0")]
+    anonymous_rate: f64,
+
+    /// Which users make the requests trawler generates for logged-in users after priming.
+    #[arg(long, default_value = "trawler")]
+    user_distribution: UserDistribution,
+
+    /// How strongly the `zipf` user distribution favors the users with the lowest ids (the
+    /// Zipf exponent of user ranks).
+    #[arg(long, default_value = "1.0")]
+    user_skew: f64,
+
+    /// How many users, those with the lowest ids, make all the requests under the `active`
+    /// user distribution.
+    #[arg(long, default_value = "100")]
+    active_users: u32,
+
+    /// Fraction of story and comment votes that withdraw the user's existing vote instead.
+    #[arg(long, default_value = "0.05")]
+    unvote_rate: f64,
+
     /// Benchmark runtime in seconds
     #[arg(short = 'r', long, default_value = "30")]
     runtime: u64,
@@ -261,7 +750,7 @@ struct Options {
     #[arg(long, default_value = "true")]
     prometheus_metrics: bool,
 
-    /// Address of a Prometheus push gate way to send metrics to. 
+    /// Address of a Prometheus push gate way to send metrics to.
     #[arg(long)]
     prometheus_push_gateway: Option<String>,
 }
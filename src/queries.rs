@@ -0,0 +1,229 @@
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::fs;
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::endpoints::QueryProvider;
+use crate::Variant;
+
+/// The SQL the pages issue, by name.
+///
+/// Every query a page runs has a `<page>.<step>` name (e.g. `frontpage.stories`). The catalog
+/// holds the queries of the selected variant's [`QueryProvider`], plus any overrides loaded
+/// from a catalog file, which allows trying out alternative queries without a rebuild.
+/// Queries built around `IN (...)` lists use named placeholders (e.g. `{stories}`) that are
+/// filled in at request time, so overrides need to keep those placeholders intact.
+///
+/// Most queries are only named where they are issued, so misspelled override names can't be
+/// caught up front; instead, the catalog keeps track of which overrides get issued, see
+/// [`QueryCatalog::unused_overrides`].
+#[derive(Debug)]
+pub(crate) struct QueryCatalog {
+    queries: HashMap<String, String>,
+    /// The names of the overridden queries, and whether they have been looked up yet.
+    overrides: HashMap<String, AtomicBool>,
+}
+
+impl QueryCatalog {
+    pub(crate) fn new(provider: &dyn QueryProvider) -> Self {
+        let queries = provider
+            .queries()
+            .iter()
+            .map(|(name, sql)| (name.to_string(), sql.to_string()))
+            .collect();
+        Self {
+            queries,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Override queries with the ones from the catalog file at `path` that apply to `variant`.
+    ///
+    /// Each query is introduced by a `-- query: <name>` line, optionally followed by a
+    /// `-- variant: <variant>` line to restrict it to a single set of queries, and ends
+    /// at the first line ending with `;`. For example:
+    ///
+    /// ```sql
+    /// -- query: frontpage.stories
+    /// -- variant: original
+    /// SELECT `stories`.* FROM `stories`
+    /// WHERE `stories`.`merged_story_id` IS NULL
+    /// ORDER BY hotness LIMIT ? OFFSET ?;
+    /// ```
+    pub(crate) fn load_overrides(&mut self, path: &str, variant: Variant) -> Result<()> {
+        let contents =
+            fs::read_to_string(path).with_context(|| format!("reading query catalog {}", path))?;
+        self.parse_overrides(path, &contents, variant)
+    }
+
+    /// Apply the overrides in `contents`, as read from `source`, that apply to `variant`.
+    fn parse_overrides(&mut self, source: &str, contents: &str, variant: Variant) -> Result<()> {
+        let mut name: Option<String> = None;
+        let mut applies = true;
+        let mut current_q = String::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if let Some(n) = line.strip_prefix("-- query:") {
+                if let Some(prev) = name {
+                    bail!("query {} in {} is missing a terminating ';'", prev, source);
+                }
+                name = Some(n.trim().to_string());
+                applies = true;
+                continue;
+            }
+            if let Some(v) = line.strip_prefix("-- variant:") {
+                let v = Variant::from_str(v.trim(), true).map_err(|e| anyhow!(e))?;
+                applies = v == variant;
+                continue;
+            }
+            if line.starts_with("--") || line.is_empty() {
+                continue;
+            }
+
+            let Some(ref n) = name else {
+                bail!("found SQL outside of a named query in {}: {}", source, line);
+            };
+            if !current_q.is_empty() {
+                current_q.push(' ');
+            }
+            current_q.push_str(line);
+            if let Some(q) = current_q.strip_suffix(';') {
+                if applies {
+                    self.queries.insert(n.clone(), q.to_string());
+                    self.overrides.insert(n.clone(), AtomicBool::new(false));
+                }
+                current_q.clear();
+                name = None;
+            }
+        }
+        if let Some(n) = name {
+            bail!("query {} in {} is missing a terminating ';'", n, source);
+        }
+
+        Ok(())
+    }
+
+    /// The SQL to issue for the query `name`, or `default` if the catalog does not override it.
+    pub(crate) fn get<'a>(&'a self, name: &str, default: &'a str) -> &'a str {
+        self.optional(name).unwrap_or(default)
+    }
+
+    /// The SQL for the query `name`, which the variant's [`QueryProvider`] must supply.
+    pub(crate) fn variant(&self, name: &str) -> &str {
+        self.optional(name)
+            .unwrap_or_else(|| panic!("no {} query for the selected variant", name))
+    }
+
+    /// The SQL for the query `name`, if the variant issues it at all.
+    pub(crate) fn optional(&self, name: &str) -> Option<&str> {
+        if !self.overrides.is_empty() {
+            if let Some(used) = self.overrides.get(name) {
+                used.store(true, Ordering::Relaxed);
+            }
+        }
+        self.queries.get(name).map(String::as_str)
+    }
+
+    /// The overrides whose queries were never issued, most likely because their names are
+    /// misspelled.
+    pub(crate) fn unused_overrides(&self) -> Vec<&str> {
+        let mut unused: Vec<_> = self
+            .overrides
+            .iter()
+            .filter(|(_, used)| !used.load(Ordering::Relaxed))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        unused.sort_unstable();
+        unused
+    }
+}
+
+/// Split a SQL script into its `;`-terminated statements, skipping comments and empty lines.
+pub(crate) fn statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current_q = String::new();
+    for line in sql.lines() {
+        if line.starts_with("--") || line.is_empty() {
+            continue;
+        }
+        if !current_q.is_empty() {
+            current_q.push(' ');
+        }
+        current_q.push_str(line);
+        if current_q.ends_with(';') {
+            statements.push(mem::take(&mut current_q));
+        }
+    }
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoints::original::Original;
+
+    const CATALOG: &str = "
+-- query: frontpage.stories
+SELECT `stories`.* FROM `stories`
+ORDER BY hotness LIMIT ? OFFSET ?;
+
+-- query: recent.stories
+-- variant: noria
+SELECT `stories`.* FROM `stories`;
+";
+
+    #[test]
+    fn overrides_apply_to_their_variant() {
+        let mut q = QueryCatalog::new(&Original);
+        let recent = q.variant("recent.stories").to_string();
+        q.parse_overrides("test", CATALOG, Variant::Original)
+            .unwrap();
+
+        assert_eq!(
+            q.variant("frontpage.stories"),
+            "SELECT `stories`.* FROM `stories` ORDER BY hotness LIMIT ? OFFSET ?"
+        );
+        assert_eq!(q.variant("recent.stories"), recent);
+        assert!(q.unused_overrides().is_empty());
+    }
+
+    #[test]
+    fn unused_overrides_are_reported() {
+        let mut q = QueryCatalog::new(&Original);
+        q.parse_overrides("test", CATALOG, Variant::Noria).unwrap();
+
+        assert_eq!(
+            q.unused_overrides(),
+            ["frontpage.stories", "recent.stories"]
+        );
+        q.get("recent.stories", "");
+        assert_eq!(q.unused_overrides(), ["frontpage.stories"]);
+    }
+
+    #[test]
+    fn malformed_overrides_are_rejected() {
+        let mut q = QueryCatalog::new(&Original);
+        assert!(q
+            .parse_overrides("test", "SELECT 1;", Variant::Original)
+            .is_err());
+        assert!(q
+            .parse_overrides("test", "-- query: a.b\nSELECT 1", Variant::Original)
+            .is_err());
+        assert!(q
+            .parse_overrides(
+                "test",
+                "-- query: a.b\n-- query: a.c\nSELECT 1;",
+                Variant::Original
+            )
+            .is_err());
+        assert!(q
+            .parse_overrides(
+                "test",
+                "-- query: a.b\n-- variant: other\nSELECT 1;",
+                Variant::Original
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Error, Row};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use trawler::UserId;
+
+use crate::queries::QueryCatalog;
+
+/// The session cookies of the users that are logged in, as their browsers would keep them.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Sessions {
+    tokens: Arc<Mutex<HashMap<UserId, String>>>,
+}
+
+impl Sessions {
+    /// Remember the session token `uid` got when logging in.
+    pub(crate) fn log_in(&self, uid: UserId, token: String) {
+        self.tokens.lock().unwrap().insert(uid, token);
+    }
+
+    /// Forget the session of `uid`.
+    pub(crate) fn log_out(&self, uid: UserId) {
+        self.tokens.lock().unwrap().remove(&uid);
+    }
+
+    /// Look up the user behind the session cookie of `uid`, like the real site does before
+    /// rendering any page for a logged-in user, returning their id.
+    ///
+    /// Users that haven't logged in during this run yet use the session token that is stored
+    /// with their account, as if their cookie survived from an earlier visit.
+    pub(crate) async fn authenticate(
+        &self,
+        mut c: Conn,
+        q: &QueryCatalog,
+        uid: UserId,
+    ) -> Result<(Conn, Option<u32>), Error> {
+        let token = self.tokens.lock().unwrap().get(&uid).cloned();
+        let token = match token {
+            Some(token) => token,
+            None => {
+                let token = c
+                    .exec_first::<String, _, _>(
+                        q.get(
+                            "session.resume",
+                            "SELECT `users`.`session_token` FROM `users` \
+                             WHERE `users`.`username` = ?",
+                        ),
+                        (format!("user{}", uid),),
+                    )
+                    .await?;
+                match token {
+                    Some(token) => {
+                        self.log_in(uid, token.clone());
+                        token
+                    }
+                    None => return Ok((c, None)),
+                }
+            }
+        };
+
+        let user = c
+            .exec_first::<Row, _, _>(
+                q.get(
+                    "session.user",
+                    "SELECT `users`.* FROM `users` \
+                     WHERE `users`.`session_token` = ? \
+                     AND `users`.`deleted_at` IS NULL",
+                ),
+                (token,),
+            )
+            .await?;
+
+        Ok((c, user.map(|user| user.get::<u32, _>("id").unwrap())))
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::{bail, Context, Result};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fs;
+
+const DEFAULT_TAGS: &str = include_str!("db-schema/tags.txt");
+
+/// Most tags a story is submitted with.
+const MAX_TAGS: usize = 4;
+
+/// Chance that a story gets yet another tag, up to [`MAX_TAGS`].
+const MORE_TAGS: f64 = 0.3;
+
+/// Most tags a user filters.
+const MAX_FILTERS: usize = 3;
+
+#[derive(Clone, Debug)]
+pub(crate) struct Tag {
+    pub(crate) tag: String,
+    pub(crate) hotness_mod: f64,
+    pub(crate) is_media: bool,
+    pub(crate) privileged: bool,
+}
+
+/// The tags that are seeded when priming, and that submissions pick from.
+///
+/// Tags are listed most popular first, and the popularity of the tag at rank `k` falls off
+/// as `1 / k^skew`, so a handful of tags end up on most stories.
+#[derive(Clone, Debug)]
+pub(crate) struct TagVocabulary {
+    tags: Vec<Tag>,
+    popularity: WeightedIndex<f64>,
+}
+
+impl TagVocabulary {
+    /// Load the vocabulary from the file at `path`, or the built-in one if there is none.
+    pub(crate) fn load(path: Option<&str>, skew: f64) -> Result<Self> {
+        match path {
+            Some(path) => {
+                let contents = fs::read_to_string(path)
+                    .with_context(|| format!("reading tag vocabulary {}", path))?;
+                Self::parse(path, &contents, skew)
+            }
+            None => Self::parse("built-in tag vocabulary", DEFAULT_TAGS, skew),
+        }
+    }
+
+    /// Parse a vocabulary of `<tag> <hotness_mod> [media] [privileged]` lines, skipping empty
+    /// lines and `#` comments.
+    fn parse(source: &str, contents: &str, skew: f64) -> Result<Self> {
+        let mut tags = Vec::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.starts_with('#') || line.is_empty() {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let tag = fields.next().unwrap();
+            if !tag
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                bail!("invalid tag {} in {}", tag, source);
+            }
+            let Some(hotness_mod) = fields.next() else {
+                bail!("tag {} in {} is missing its hotness_mod", tag, source);
+            };
+            let hotness_mod = hotness_mod
+                .parse()
+                .with_context(|| format!("hotness_mod of tag {} in {}", tag, source))?;
+
+            let mut is_media = false;
+            let mut privileged = false;
+            for flag in fields {
+                match flag {
+                    "media" => is_media = true,
+                    "privileged" => privileged = true,
+                    _ => bail!("unknown flag {} for tag {} in {}", flag, tag, source),
+                }
+            }
+
+            if tags.iter().any(|t: &Tag| t.tag == tag) {
+                bail!("duplicate tag {} in {}", tag, source);
+            }
+            tags.push(Tag {
+                tag: tag.to_string(),
+                hotness_mod,
+                is_media,
+                privileged,
+            });
+        }
+        if tags.iter().all(|tag| tag.privileged) {
+            bail!(
+                "no tags in {} that users other than moderators can use",
+                source
+            );
+        }
+
+        let popularity = WeightedIndex::new((1..=tags.len()).map(|k| 1.0 / (k as f64).powf(skew)))
+            .with_context(|| format!("tag skew {}", skew))?;
+        Ok(Self { tags, popularity })
+    }
+
+    pub(crate) fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Pick the (distinct) tags for a new story: usually one, sometimes several. Privileged tags
+    /// are only picked for moderators.
+    pub(crate) fn pick<R: Rng>(&self, rng: &mut R, moderator: bool) -> Vec<String> {
+        let usable = self
+            .tags
+            .iter()
+            .filter(|tag| moderator || !tag.privileged)
+            .count();
+        let mut n = 1;
+        while n < MAX_TAGS.min(usable) && rng.gen_bool(MORE_TAGS) {
+            n += 1;
+        }
+
+        let mut picked = Vec::with_capacity(n);
+        while picked.len() < n {
+            let tag = &self.tags[self.popularity.sample(rng)];
+            if (moderator || !tag.privileged) && !picked.contains(&tag.tag) {
+                picked.push(tag.tag.clone());
+            }
+        }
+        picked
+    }
+
+    /// Pick a tag to browse the stories of, favoring the more popular ones.
+    pub(crate) fn pick_one<R: Rng>(&self, rng: &mut R) -> String {
+        self.tags[self.popularity.sample(rng)].tag.clone()
+    }
+
+    /// Pick the (distinct) tags a user filters. Which tags users dislike has little to do
+    /// with how popular they are, so these are picked uniformly.
+    pub(crate) fn pick_filters<R: Rng>(&self, rng: &mut R) -> Vec<String> {
+        let n = rng.gen_range(1..=MAX_FILTERS.min(self.tags.len()));
+        self.tags
+            .choose_multiple(rng, n)
+            .map(|tag| tag.tag.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_hotness_mods_and_flags() {
+        let vocabulary = TagVocabulary::parse(
+            "test",
+            "# comment\n\nrust 0.5\nvideo -0.25 media\nannounce 0 privileged\n",
+            1.0,
+        )
+        .unwrap();
+
+        let tags = vocabulary.tags();
+        assert_eq!(tags.len(), 3);
+        assert_eq!(tags[0].tag, "rust");
+        assert_eq!(tags[0].hotness_mod, 0.5);
+        assert!(!tags[0].is_media && !tags[0].privileged);
+        assert_eq!(tags[1].hotness_mod, -0.25);
+        assert!(tags[1].is_media);
+        assert!(tags[2].privileged);
+    }
+
+    #[test]
+    fn parse_rejects_malformed_vocabularies() {
+        for contents in [
+            "rust",
+            "rust fast",
+            "rust 0 shiny",
+            "r&d 0",
+            "rust 0\nrust 1",
+            "announce 0 privileged",
+            "",
+        ] {
+            assert!(
+                TagVocabulary::parse("test", contents, 1.0).is_err(),
+                "{:?}",
+                contents
+            );
+        }
+    }
+
+    #[test]
+    fn only_moderators_pick_privileged_tags() {
+        let vocabulary =
+            TagVocabulary::parse("test", "announce 0 privileged\nrust 0\n", 1.0).unwrap();
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            assert_eq!(vocabulary.pick(&mut rng, false), ["rust"]);
+        }
+        let announced = (0..100).any(|_| {
+            let picked = vocabulary.pick(&mut rng, true);
+            picked.iter().any(|tag| tag == "announce")
+        });
+        assert!(announced);
+    }
+}
@@ -0,0 +1,121 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::endpoints;
+
+/// Sites that submitted stories link to.
+const DOMAINS: &[&str] = &[
+    "github.com",
+    "blog.example.com",
+    "news.example.org",
+    "www.example.net",
+    "research.example.edu",
+    "example.dev",
+];
+
+/// How many of the most recently submitted URLs duplicates are picked from.
+const RECENT_URLS: usize = 1000;
+
+/// Length of the random path of a generated URL.
+const PATH_LEN: usize = 12;
+
+/// The URLs of submitted stories: most submissions link somewhere, and some link to the same
+/// place a recent submission did, if not always spelled quite the same way.
+#[derive(Clone, Debug)]
+pub(crate) struct Urls {
+    rate: f64,
+    duplicate_rate: f64,
+    recent: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl Urls {
+    pub(crate) fn new(rate: f64, duplicate_rate: f64) -> Self {
+        Self {
+            rate,
+            duplicate_rate,
+            recent: Default::default(),
+        }
+    }
+
+    /// Pick the URL of a new submission, or `None` for a text post.
+    pub(crate) fn pick<R: Rng>(&self, rng: &mut R) -> Option<String> {
+        if rng.gen::<f64>() >= self.rate {
+            return None;
+        }
+
+        let mut recent = self.recent.lock().unwrap();
+        if !recent.is_empty() && rng.gen::<f64>() < self.duplicate_rate {
+            let i = rng.gen_range(0..recent.len());
+            let variants = variants(&recent[i]);
+            return variants.choose(rng).cloned();
+        }
+
+        let scheme = if rng.gen_bool(0.8) { "https" } else { "http" };
+        let url = format!(
+            "{}://{}/{}",
+            scheme,
+            DOMAINS.choose(rng).unwrap(),
+            endpoints::token(rng, PATH_LEN)
+        );
+        if recent.len() == RECENT_URLS {
+            recent.pop_front();
+        }
+        recent.push_back(url.clone());
+        Some(url)
+    }
+}
+
+/// The spellings of `url` that the real site considers the same when looking for a previous
+/// submission of it: either scheme, with or without a trailing slash.
+pub(crate) fn variants(url: &str) -> Vec<String> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"));
+    let schemes = match rest {
+        Some(rest) => vec![format!("https://{}", rest), format!("http://{}", rest)],
+        None => vec![url.to_string()],
+    };
+
+    let mut urls = Vec::new();
+    for url in schemes {
+        let trimmed = url.trim_end_matches('/');
+        for url in [trimmed.to_string(), format!("{}/", trimmed)] {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+    }
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variants_cover_schemes_and_trailing_slashes() {
+        assert_eq!(
+            variants("https://example.dev/a"),
+            [
+                "https://example.dev/a",
+                "https://example.dev/a/",
+                "http://example.dev/a",
+                "http://example.dev/a/",
+            ]
+        );
+        assert_eq!(
+            variants("http://example.dev/a/"),
+            variants("https://example.dev/a")
+        );
+    }
+
+    #[test]
+    fn variants_keep_unknown_schemes() {
+        assert_eq!(
+            variants("ftp://example.dev/a/"),
+            ["ftp://example.dev/a", "ftp://example.dev/a/"]
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use metrics::gauge;
+use mysql_async::prelude::*;
+use mysql_async::{Error, Pool};
+use rand::distributions::Distribution;
+use rand::Rng;
+use rand_distr::Zipf;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use trawler::UserId;
+
+use crate::queries::QueryCatalog;
+
+/// Which users the requests trawler generates for logged-in users are made by.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ValueEnum)]
+pub(crate) enum UserDistribution {
+    /// The users trawler picks.
+    Trawler,
+    /// Any user, all of them equally likely.
+    Uniform,
+    /// The user at rank `k` (starting at 1 for user 0) with probability proportional to
+    /// `1 / k^skew`.
+    Zipf,
+    /// A fixed set of active users, all of them equally likely.
+    Active,
+}
+
+/// The users that requests are made by, remapped from the users trawler picks.
+///
+/// The users to pick from are the ones in the database when the first request after priming
+/// comes along, plus any others trawler uses later on. Logging in and out is left to the
+/// users trawler picks, and the distinct users the other requests are made by are reported in
+/// the `lobsters_distinct_users` metric.
+#[derive(Clone, Debug)]
+pub(crate) struct ActingUsers {
+    distribution: UserDistribution,
+    skew: f64,
+    active: UserId,
+    seen: Arc<Mutex<Seen>>,
+}
+
+#[derive(Debug, Default)]
+struct Seen {
+    /// Whether the population has been looked up in the database yet.
+    seeded: bool,
+    /// How many users there are to pick from, with ids starting at 0.
+    population: UserId,
+    /// The users requests were made by after priming.
+    users: HashSet<UserId>,
+    /// The Zipf distribution over the population, and the population it was built for.
+    zipf: Option<(UserId, Zipf<f64>)>,
+}
+
+impl ActingUsers {
+    pub(crate) fn new(distribution: UserDistribution, skew: f64, active: UserId) -> Result<Self> {
+        if let Err(e) = Zipf::new(1, skew) {
+            bail!("invalid user skew {}: {}", skew, e);
+        }
+        if active == 0 {
+            bail!("there must be at least one active user");
+        }
+        Ok(Self {
+            distribution,
+            skew,
+            active,
+            seen: Default::default(),
+        })
+    }
+
+    /// Look up how many users there are, the first time around. Users are numbered from 0 by
+    /// trawler and from 1 in the database, so the highest database id is the number of users.
+    pub(crate) async fn seed(&self, pool: &Pool, q: &QueryCatalog) -> Result<(), Error> {
+        let seeded = self.seen.lock().unwrap().seeded;
+        if self.distribution == UserDistribution::Trawler || seeded {
+            return Ok(());
+        }
+
+        let mut c = pool.get_conn().await?;
+        let users = c
+            .query_first::<Option<UserId>, _>(
+                q.get("users.population", "SELECT MAX(`users`.`id`) FROM `users`"),
+            )
+            .await?
+            .flatten()
+            .unwrap_or(0);
+
+        let mut seen = self.seen.lock().unwrap();
+        seen.population = seen.population.max(users);
+        seen.seeded = true;
+        Ok(())
+    }
+
+    /// The user that makes the request trawler generated for `uid`.
+    pub(crate) fn remap<R: Rng>(&self, rng: &mut R, uid: UserId) -> UserId {
+        let mut seen = self.seen.lock().unwrap();
+        seen.population = seen.population.max(uid + 1);
+
+        let uid = match self.distribution {
+            UserDistribution::Trawler => uid,
+            UserDistribution::Uniform => rng.gen_range(0..seen.population),
+            UserDistribution::Zipf => {
+                let population = seen.population;
+                let zipf = match seen.zipf {
+                    Some((n, zipf)) if n == population => zipf,
+                    _ => {
+                        // the skew was checked when starting out
+                        let zipf = Zipf::new(population as u64, self.skew).unwrap();
+                        seen.zipf = Some((population, zipf));
+                        zipf
+                    }
+                };
+                zipf.sample(rng) as UserId - 1
+            }
+            UserDistribution::Active => rng.gen_range(0..self.active.min(seen.population)),
+        };
+
+        if seen.users.insert(uid) {
+            gauge!("lobsters_distinct_users").set(seen.users.len() as f64);
+        }
+        uid
+    }
+
+    /// How many distinct users requests were made by after priming.
+    pub(crate) fn distinct(&self) -> usize {
+        self.seen.lock().unwrap().users.len()
+    }
+}